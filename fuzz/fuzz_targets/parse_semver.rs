@@ -0,0 +1,12 @@
+#![no_main]
+//! Run with `cargo +nightly fuzz run parse_semver` from the repository root
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &str| {
+    if let Ok(version) = gitflow_rs::parse_semver(data) {
+        // Anything that parses must format back to a string that parses to the same version
+        let formatted = version.get_semver().unwrap();
+        assert_eq!(gitflow_rs::parse_semver(&formatted).unwrap(), version);
+    }
+});
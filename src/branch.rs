@@ -0,0 +1,724 @@
+//! Classification of branch names into their gitflow roles
+
+use crate::{
+    get_info_for_repo, resolve_version, GitflowConfig, GitflowError, SemverBase, TagIndex,
+    VersionInfo,
+};
+use git2::{BranchType, Oid, Repository};
+
+/// The role a branch plays in gitflow, as determined by its name
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum BranchClass {
+    /// Feature branch, any branch that doesn't fall under the other categories
+    Feature,
+
+    /// Release branch (`vX.Y.Z` or `release/vX.Y.Z`)
+    Release(SemverBase),
+
+    /// Hotfix branch (`hotfix/vX.Y.Z`)
+    Hotfix(SemverBase),
+
+    /// Release train branch (`<prefix><id>`, see [`GitflowConfig::release_train_prefix`])
+    Train(SemverBase),
+
+    /// Development branch (develop)
+    Develop,
+
+    /// Production branch (master or main)
+    Production,
+}
+
+impl BranchClass {
+    /// Returns a short, stable label for this kind of branch, intended for compact displays such
+    /// as shell prompts and status lines.
+    ///
+    /// | Class        | Label    |
+    /// |--------------|----------|
+    /// | `Feature`    | `feat`   |
+    /// | `Release`    | `rel`    |
+    /// | `Hotfix`     | `hotfix` |
+    /// | `Train`      | `train`  |
+    /// | `Develop`    | `dev`    |
+    /// | `Production` | `prod`   |
+    ///
+    /// These labels are part of the public API and will not change.
+    pub fn short_label(&self) -> &'static str {
+        match &self {
+            BranchClass::Feature => "feat",
+            BranchClass::Release(_) => "rel",
+            BranchClass::Hotfix(_) => "hotfix",
+            BranchClass::Train(_) => "train",
+            BranchClass::Develop => "dev",
+            BranchClass::Production => "prod",
+        }
+    }
+}
+
+/// Parses a branch name of the form `vX.Y.Z` into its base version
+fn parse_branch_version(name: &str, config: &GitflowConfig) -> Option<SemverBase> {
+    match config.parse_version(name) {
+        Some(VersionInfo::Production(base)) => Some(base),
+        _ => None,
+    }
+}
+
+/// Parses a release train identifier into the base version of the train. Identifiers are either
+/// a train number `N`, giving `vN.0.0`, or a date like `YY.MM` giving `vYY.MM.0`. Four digit years
+/// are counted from 2000, so `2024.05` is `v24.5.0`.
+fn parse_train_id(id: &str) -> Option<SemverBase> {
+    let component = |part: &str| -> Option<u8> {
+        if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        part.parse().ok()
+    };
+    let mut parts = id.split('.');
+    let major = parts.next()?;
+    let major = if major.len() == 4 {
+        let year: u16 = major.parse().ok()?;
+        year.checked_sub(2000)?.try_into().ok()?
+    } else {
+        component(major)?
+    };
+    let minor = match parts.next() {
+        Some(minor) => component(minor)?,
+        None => 0,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(SemverBase {
+        major,
+        minor,
+        patch: 0,
+    })
+}
+
+/// Returns the base version of the release train named by the branch or tag `name`, or `None` if
+/// release trains aren't configured or `name` isn't a train
+pub(crate) fn parse_train(name: &str, config: &GitflowConfig) -> Option<SemverBase> {
+    parse_train_id(name.strip_prefix(config.release_train_prefix.as_deref()?)?)
+}
+
+/// Returns the branch a merge queue ref such as `gh-readonly-queue/main/pr-123-<sha>` will merge
+/// into, or `None` if `name` isn't a merge queue ref
+fn merge_queue_target<'a>(name: &'a str, config: &GitflowConfig) -> Option<&'a str> {
+    let rest = name.strip_prefix(config.merge_queue_prefix.as_deref()?)?;
+    let (target, _) = rest.rsplit_once("/pr-")?;
+    Some(target).filter(|target| !target.is_empty())
+}
+
+/// Determines the gitflow role of the branch named `name`. Merge queue refs are classified as the
+/// branch they target.
+pub fn classify_branch(name: &str, config: &GitflowConfig) -> BranchClass {
+    let name = merge_queue_target(name, config).unwrap_or(name);
+    match name {
+        "master" | "main" => return BranchClass::Production,
+        "develop" => return BranchClass::Develop,
+        _ => {}
+    }
+
+    if let Some(base) = parse_train(name, config) {
+        return BranchClass::Train(base);
+    }
+
+    if let Some(version) = name.strip_prefix("hotfix/") {
+        if let Some(base) = parse_branch_version(version, config) {
+            return BranchClass::Hotfix(base);
+        }
+    }
+
+    let version = name.strip_prefix("release/").unwrap_or(name);
+    match parse_branch_version(version, config) {
+        Some(base) => BranchClass::Release(base),
+        None => BranchClass::Feature,
+    }
+}
+
+/// Extracts the base version from the name of a release or hotfix branch, such as `v1.2.3` from
+/// `release/v1.2.3`, `hotfix/v1.2.3`, or a bare `v1.2.3`, using the `tag_regex` of `config` if
+/// the `regex` feature is enabled and it is set. Unlike
+/// [`classify_branch`] this only looks at the name itself, so merge queue refs and release trains
+/// give `None`, as do develop, production, and feature branches.
+pub fn base_from_branch(name: &str, config: &GitflowConfig) -> Option<SemverBase> {
+    let version = name
+        .strip_prefix("hotfix/")
+        .or_else(|| name.strip_prefix("release/"))
+        .unwrap_or(name);
+    parse_branch_version(version, config)
+}
+
+/// File at the root of the working tree listing branches to leave out of versioning
+pub const BRANCH_IGNORE_FILE: &str = ".gitflowignore";
+
+/// Patterns of the [`BRANCH_IGNORE_FILE`], matched against branch names.
+///
+/// The file follows gitignore syntax, with branch names in the place of paths: one pattern per
+/// line, blank lines and lines starting with `#` are skipped, and a leading `!` re-includes
+/// branches an earlier pattern ignored. Patterns support `*` and `?`, which don't match `/`, and
+/// `**`, which does. A pattern containing a `/` is matched against the whole name, and one without
+/// against each `/` separated component. As with directories, ignoring a prefix of a name ignores
+/// the name, so both `tmp/*` and `tmp/` ignore `tmp/a/b`. The last matching pattern decides.
+///
+/// Ignored branches are left out everywhere branches are listed, such as finding develop and
+/// production, [`all_branch_versions`], and [`looks_like_gitflow`], and are classified as feature
+/// branches when versioned directly.
+#[derive(Debug, Default)]
+pub(crate) struct BranchIgnore {
+    /// Each pattern with whether it re-includes matching branches
+    patterns: Vec<(String, bool)>,
+}
+
+impl BranchIgnore {
+    /// Reads the [`BRANCH_IGNORE_FILE`] of `repo`, which ignores nothing if it is missing or the
+    /// repository is bare
+    pub(crate) fn read(repo: &Repository) -> Result<Self, GitflowError> {
+        let path = match repo.workdir() {
+            Some(workdir) => workdir.join(BRANCH_IGNORE_FILE),
+            None => return Ok(Self::default()),
+        };
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Self::parse(&contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(GitflowError::Io(err)),
+        }
+    }
+
+    fn parse(contents: &str) -> Self {
+        let patterns = contents
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| match line.strip_prefix('!') {
+                Some(pattern) => (pattern.to_owned(), true),
+                None => (line.to_owned(), false),
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Returns true if the branch `name` is ignored
+    pub(crate) fn ignores(&self, name: &str) -> bool {
+        let mut ignored = false;
+        for (pattern, negated) in &self.patterns {
+            if ignore_pattern_matches(pattern, name) {
+                ignored = !negated;
+            }
+        }
+        ignored
+    }
+}
+
+/// Matches a single gitignore style `pattern` against the branch `name` or any of its prefixes
+/// ending at a `/`
+fn ignore_pattern_matches(pattern: &str, name: &str) -> bool {
+    let (pattern, prefix_only) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    let mut candidates: Vec<&str> = name.match_indices('/').map(|(i, _)| &name[..i]).collect();
+    if !prefix_only {
+        candidates.push(name);
+    }
+    candidates.into_iter().any(|candidate| {
+        if anchored {
+            glob_matches(pattern.as_bytes(), candidate.as_bytes())
+        } else {
+            let component = candidate.rsplit('/').next().unwrap_or(candidate);
+            glob_matches(pattern.as_bytes(), component.as_bytes())
+        }
+    })
+}
+
+/// Matches `text` against a glob with `*`, `**` and `?`
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let end = text.iter().position(|&c| c == b'/').unwrap_or(text.len());
+            (0..=end).any(|i| glob_matches(rest, &text[i..]))
+        }
+        [b'?', rest @ ..] => match text {
+            [c, text @ ..] if *c != b'/' => glob_matches(rest, text),
+            _ => false,
+        },
+        [p, rest @ ..] => match text {
+            [c, text @ ..] if c == p => glob_matches(rest, text),
+            _ => false,
+        },
+    }
+}
+
+/// Lists the name and tip commit of every local branch in `repo`, leaving out branches ignored by
+/// the [`BRANCH_IGNORE_FILE`]
+pub(crate) fn local_branches(repo: &Repository) -> Result<Vec<(String, Oid)>, GitflowError> {
+    let ignore = BranchIgnore::read(repo)?;
+    let mut branches = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        let (branch, _) = branch?;
+        if let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) {
+            if !ignore.ignores(name) {
+                branches.push((name.to_owned(), tip));
+            }
+        }
+    }
+    Ok(branches)
+}
+
+/// Heuristically decides whether `repo` follows gitflow, so tools supporting several workflows
+/// can choose between gitflow versioning and a fallback such as trunk based versioning.
+///
+/// A repository looks like gitflow if any of these exist:
+/// - A `develop` branch
+/// - A release or hotfix branch, such as `release/v1.2.3` or `hotfix/v1.2.4`
+/// - A release candidate tag, such as `v1.2.3-rc.1`
+///
+/// Both local and remote tracking branches are considered, as CI checkouts often only have the
+/// latter. Production tags alone don't count since trunk based repositories tag releases too.
+pub fn looks_like_gitflow(repo: &Repository, config: &GitflowConfig) -> Result<bool, GitflowError> {
+    let ignore = BranchIgnore::read(repo)?;
+    for branch in repo.branches(None)? {
+        let (branch, kind) = branch?;
+        let name = match branch.name()? {
+            Some(name) => name,
+            None => continue,
+        };
+        let name = match kind {
+            BranchType::Remote => match name.split_once('/') {
+                Some((_, name)) => name,
+                None => continue,
+            },
+            BranchType::Local => name,
+        };
+        if ignore.ignores(name) {
+            continue;
+        }
+        if matches!(
+            classify_branch(name, config),
+            BranchClass::Develop | BranchClass::Release(_) | BranchClass::Hotfix(_)
+        ) {
+            return Ok(true);
+        }
+    }
+    let tags = TagIndex::new(repo, config)?;
+    Ok(tags
+        .tags()
+        .iter()
+        .any(|tag| matches!(tag.version, VersionInfo::Alpha(_))))
+}
+
+/// Determines the version the branch `target` currently has, such as the live version of `main`
+/// while building a pull request into it, so checks can compare it with the version the pull
+/// request produces. The local branch is used if there is one, otherwise `origin/<target>`, as
+/// CI checkouts of pull requests often only fetch the target as a remote tracking branch. Channel
+/// overrides from the environment and the channel file aren't applied, they describe the build
+/// rather than the target.
+///
+/// Returns `None` if neither branch exists, such as in an offline build whose target was never
+/// fetched, as nothing is fetched to find it.
+pub fn target_branch_version(
+    repo: &Repository,
+    target: &str,
+    config: &GitflowConfig,
+) -> Result<Option<VersionInfo>, GitflowError> {
+    let branch = repo
+        .find_branch(target, BranchType::Local)
+        .or_else(|_| repo.find_branch(&format!("origin/{}", target), BranchType::Remote));
+    let branch = match branch {
+        Ok(branch) => branch,
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let tip = branch.get().peel_to_commit()?.id();
+    let tags = TagIndex::new(repo, config)?;
+    resolve_version(repo, target, tip, None, &tags, config).map(Some)
+}
+
+/// Versions of every local branch in a repository
+#[derive(Debug, Default)]
+pub struct BranchVersions {
+    /// Branch name and the version of its tip commit, for each branch that could be classified
+    pub versions: Vec<(String, VersionInfo)>,
+
+    /// Branch name and the reason it couldn't be classified
+    pub errors: Vec<(String, GitflowError)>,
+}
+
+/// Classifies the tip commit of every local branch in `repo`, giving a snapshot of where each
+/// branch sits version-wise. Branches that fail to classify are reported in
+/// [`BranchVersions::errors`] rather than failing the whole scan.
+pub fn all_branch_versions(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<BranchVersions, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut result = BranchVersions::default();
+    for (name, tip) in local_branches(repo)? {
+        let version = repo
+            .find_commit(tip)
+            .map_err(GitflowError::from)
+            .and_then(|commit| resolve_version(repo, &name, commit.id(), None, &tags, config));
+        match version {
+            Ok(version) => result.versions.push((name, version)),
+            Err(err) => result.errors.push((name, err)),
+        }
+    }
+    Ok(result)
+}
+
+/// Turns a branch name into dot separated semver pre-release identifiers. Runs of characters that
+/// aren't allowed in identifiers become separators, and leading zeros are stripped from numeric
+/// identifiers, as semver forbids them.
+fn prerelease_identifiers(name: &str) -> Vec<&str> {
+    name.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            if part.bytes().all(|b| b.is_ascii_digit()) {
+                let trimmed = part.trim_start_matches('0');
+                if trimmed.is_empty() {
+                    "0"
+                } else {
+                    trimmed
+                }
+            } else {
+                part
+            }
+        })
+        .collect()
+}
+
+/// Builds an installable semver version for HEAD on a non-release branch, such as
+/// `1.3.0-feature.login.42` for build 42 of `feature/login`. The pre-release is made of the
+/// sanitized branch name followed by the build number, so it is always valid semver and sorts
+/// below the `base` release. There is no leading `v`, as package managers like npm and cargo
+/// expect bare versions.
+pub fn feature_prerelease_version(
+    repo: &Repository,
+    base: SemverBase,
+    config: &GitflowConfig,
+) -> Result<String, GitflowError> {
+    let info = get_info_for_repo(repo, config)?;
+    let mut identifiers = prerelease_identifiers(&info.branch_name);
+    let build = info.build_number.to_string();
+    identifiers.push(&build);
+    Ok(format!(
+        "{}.{}.{}-{}",
+        base.major,
+        base.minor,
+        base.patch,
+        identifiers.join(".")
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util::TestRepo, SemverRC};
+
+    const BASE: SemverBase = SemverBase {
+        major: 1,
+        minor: 2,
+        patch: 3,
+    };
+
+    #[test]
+    fn classify_names() {
+        let config = GitflowConfig::default();
+        assert_eq!(classify_branch("main", &config), BranchClass::Production);
+        assert_eq!(classify_branch("master", &config), BranchClass::Production);
+        assert_eq!(classify_branch("develop", &config), BranchClass::Develop);
+        assert_eq!(
+            classify_branch("v1.2.3", &config),
+            BranchClass::Release(BASE)
+        );
+        assert_eq!(
+            classify_branch("release/v1.2.3", &config),
+            BranchClass::Release(BASE)
+        );
+        assert_eq!(
+            classify_branch("hotfix/v1.2.3", &config),
+            BranchClass::Hotfix(BASE)
+        );
+        assert_eq!(classify_branch("login-page", &config), BranchClass::Feature);
+        assert_eq!(
+            classify_branch("feature/v1.2.3", &config),
+            BranchClass::Feature
+        );
+        assert_eq!(
+            classify_branch("v1.2.3-rc.1", &config),
+            BranchClass::Feature
+        );
+    }
+
+    #[test]
+    fn classify_merge_queue_refs() {
+        let config = GitflowConfig::default();
+        assert_eq!(
+            classify_branch("gh-readonly-queue/main/pr-123-0123456789abcdef", &config),
+            BranchClass::Production
+        );
+        assert_eq!(
+            classify_branch("gh-readonly-queue/release/v1.2.3/pr-7-abcdef", &config),
+            BranchClass::Release(BASE)
+        );
+        assert_eq!(
+            classify_branch("gh-readonly-queue/develop", &config),
+            BranchClass::Feature
+        );
+
+        let config = GitflowConfig {
+            merge_queue_prefix: Some("merge-queue/".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(
+            classify_branch("merge-queue/develop/pr-1-abc", &config),
+            BranchClass::Develop
+        );
+        assert_eq!(
+            classify_branch("gh-readonly-queue/main/pr-123-abc", &config),
+            BranchClass::Feature
+        );
+    }
+
+    #[test]
+    fn classify_trains() {
+        let train = |major, minor| {
+            BranchClass::Train(SemverBase {
+                major,
+                minor,
+                patch: 0,
+            })
+        };
+        assert_eq!(
+            classify_branch("train/2024.05", &GitflowConfig::default()),
+            BranchClass::Feature
+        );
+        let config = GitflowConfig {
+            release_train_prefix: Some("train/".to_owned()),
+            ..Default::default()
+        };
+        assert_eq!(classify_branch("train/2024.05", &config), train(24, 5));
+        assert_eq!(classify_branch("train/24.05", &config), train(24, 5));
+        assert_eq!(classify_branch("train/42", &config), train(42, 0));
+        assert_eq!(
+            classify_branch("train/1999.01", &config),
+            BranchClass::Feature
+        );
+        assert_eq!(
+            classify_branch("train/24.05.1", &config),
+            BranchClass::Feature
+        );
+        assert_eq!(classify_branch("train/next", &config), BranchClass::Feature);
+        assert_eq!(classify_branch("develop", &config), BranchClass::Develop);
+    }
+
+    #[test]
+    fn branch_bases() {
+        let config = GitflowConfig::default();
+        assert_eq!(base_from_branch("release/v1.2.3", &config), Some(BASE));
+        assert_eq!(base_from_branch("hotfix/v1.2.3", &config), Some(BASE));
+        assert_eq!(base_from_branch("v1.2.3", &config), Some(BASE));
+        assert_eq!(base_from_branch("release/v1.2.3-rc.1", &config), None);
+        assert_eq!(base_from_branch("feature/v1.2.3", &config), None);
+        assert_eq!(base_from_branch("feature/login", &config), None);
+        assert_eq!(base_from_branch("develop", &config), None);
+        assert_eq!(base_from_branch("main", &config), None);
+    }
+
+    #[test]
+    fn short_labels() {
+        assert_eq!(BranchClass::Feature.short_label(), "feat");
+        assert_eq!(BranchClass::Release(BASE).short_label(), "rel");
+        assert_eq!(BranchClass::Hotfix(BASE).short_label(), "hotfix");
+        assert_eq!(BranchClass::Train(BASE).short_label(), "train");
+        assert_eq!(BranchClass::Develop.short_label(), "dev");
+        assert_eq!(BranchClass::Production.short_label(), "prod");
+    }
+
+    #[test]
+    fn versions_of_all_branches() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.2.3");
+        repo.commit("fix");
+        repo.tag("v1.2.3-rc.1");
+        repo.checkout("develop");
+        repo.branch("login-page");
+
+        let branches = all_branch_versions(&repo.repo, &GitflowConfig::default()).unwrap();
+        let mut versions = branches.versions;
+        versions.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            versions,
+            vec![
+                ("develop".to_owned(), VersionInfo::Development),
+                ("login-page".to_owned(), VersionInfo::Local),
+                (
+                    "release/v1.2.3".to_owned(),
+                    VersionInfo::Alpha(SemverRC { base: BASE, rc: 1 })
+                ),
+            ]
+        );
+
+        // main has no production tag yet
+        assert_eq!(branches.errors.len(), 1);
+        assert_eq!(branches.errors[0].0, "main");
+    }
+
+    #[test]
+    fn branch_ignore_patterns() {
+        let ignore = BranchIgnore::parse("# throwaway work\ntmp/*\nbackup/\n!backup/keep\nwip-*\n");
+        assert!(ignore.ignores("tmp/try"));
+        assert!(ignore.ignores("tmp/try/again"));
+        assert!(!ignore.ignores("tmp"));
+        assert!(ignore.ignores("backup/old"));
+        assert!(!ignore.ignores("backup/keep"));
+        assert!(ignore.ignores("wip-login"));
+        assert!(ignore.ignores("feature/wip-login"));
+        assert!(!ignore.ignores("develop"));
+        assert!(!ignore.ignores("release/v1.2.3"));
+
+        let ignore = BranchIgnore::parse("release/**/old\n/release/v9.?.?\n");
+        assert!(ignore.ignores("release/a/b/old"));
+        assert!(ignore.ignores("release/v9.0.0"));
+        assert!(!ignore.ignores("release/v1.2.3"));
+    }
+
+    #[test]
+    fn ignored_branches() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("tmp/release/v1.2.3");
+        repo.checkout("develop");
+        repo.branch("release/v9.0.0");
+        std::fs::write(
+            repo.dir.path().join(BRANCH_IGNORE_FILE),
+            "tmp/\nrelease/v9*\nmain\n",
+        )
+        .unwrap();
+
+        let branches = all_branch_versions(&repo.repo, &GitflowConfig::default()).unwrap();
+        assert_eq!(
+            branches.versions,
+            [("develop".to_owned(), VersionInfo::Development)]
+        );
+        assert!(branches.errors.is_empty());
+        assert_eq!(
+            get_info_for_repo(&repo.repo, &GitflowConfig::default())
+                .unwrap()
+                .version,
+            VersionInfo::Local
+        );
+    }
+
+    #[test]
+    fn sanitized_prerelease_identifiers() {
+        assert_eq!(
+            prerelease_identifiers("feature/login"),
+            ["feature", "login"]
+        );
+        assert_eq!(
+            prerelease_identifiers("user/jo_smith//fix-007"),
+            ["user", "jo", "smith", "fix-007"]
+        );
+        assert_eq!(
+            prerelease_identifiers("issue/0042/00"),
+            ["issue", "42", "0"]
+        );
+        assert!(prerelease_identifiers("/_/").is_empty());
+    }
+
+    #[test]
+    fn feature_prerelease() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.branch("feature/login");
+        repo.commit("login page");
+        let base = SemverBase {
+            major: 1,
+            minor: 3,
+            patch: 0,
+        };
+        let version =
+            feature_prerelease_version(&repo.repo, base, &GitflowConfig::default()).unwrap();
+        assert_eq!(version, "1.3.0-feature.login.2");
+
+        let version = semver::Version::parse(&version).unwrap();
+        assert!(version < semver::Version::new(1, 3, 0));
+        assert!(version > semver::Version::new(1, 2, 0));
+    }
+
+    #[test]
+    fn gitflow_repo_detection() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("login-page");
+        assert!(!looks_like_gitflow(&repo.repo, &GitflowConfig::default()).unwrap());
+
+        repo.tag("v1.1.0-rc.1");
+        assert!(looks_like_gitflow(&repo.repo, &GitflowConfig::default()).unwrap());
+
+        let repo = TestRepo::new();
+        let initial = repo.commit("initial");
+        repo.repo
+            .reference("refs/remotes/origin/develop", initial, false, "fetch")
+            .unwrap();
+        assert!(looks_like_gitflow(&repo.repo, &GitflowConfig::default()).unwrap());
+
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("hotfix/v1.0.1");
+        assert!(looks_like_gitflow(&repo.repo, &GitflowConfig::default()).unwrap());
+    }
+
+    #[test]
+    fn target_branch_versions() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.2.3");
+        repo.branch("develop");
+        repo.branch("feature/login");
+        repo.commit("login page");
+        assert_eq!(
+            target_branch_version(&repo.repo, "main", &GitflowConfig::default()).unwrap(),
+            Some(VersionInfo::Production(BASE))
+        );
+        assert_eq!(
+            target_branch_version(&repo.repo, "develop", &GitflowConfig::default()).unwrap(),
+            Some(VersionInfo::Development)
+        );
+
+        // Only a remote tracking branch of the target
+        let tip = repo.repo.head().unwrap().target().unwrap();
+        repo.repo
+            .reference("refs/remotes/origin/release/v1.3.0", tip, false, "fetch")
+            .unwrap();
+        assert_eq!(
+            target_branch_version(&repo.repo, "release/v1.3.0", &GitflowConfig::default())
+                .unwrap()
+                .unwrap()
+                .get_semver()
+                .unwrap(),
+            "v1.3.0-rc.1"
+        );
+
+        // Never fetched, as in an offline build
+        let config = GitflowConfig {
+            offline: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            target_branch_version(&repo.repo, "missing", &config).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Reading versions out of git bundles
+
+use crate::{get_info_with_config, GitflowConfig, GitflowError, GitflowInfo};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Temporary directory that is removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Result<Self, GitflowError> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos());
+        let path =
+            std::env::temp_dir().join(format!("gitflow-bundle-{}-{}", std::process::id(), nanos));
+        std::fs::create_dir(&path).map_err(GitflowError::Io)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Determines the version of the commit a git bundle points HEAD at, for builds that ship as a
+/// `.bundle` rather than a repository.
+///
+/// libgit2 can't read bundles, so the bundle is unpacked into a temporary bare repository with
+/// the `git` command line tool, which must be installed. A bundle only carries the refs it was
+/// created with, so it must include `HEAD` and should be created with `--all` (or at least the
+/// branches and tags the version depends on). Missing tags give lower or missing versions and
+/// missing branches change what develop and release branches are known, exactly as they would in
+/// a repository without those refs. As there is no working tree, the
+/// [`CHANNEL_FILE`](crate::CHANNEL_FILE) is never read.
+///
+/// Uses the default configuration, see [`get_info_from_bundle_with_config`].
+pub fn get_info_from_bundle(path: &Path) -> Result<GitflowInfo, GitflowError> {
+    get_info_from_bundle_with_config(path, &GitflowConfig::default())
+}
+
+/// Determines the version of the commit a git bundle points HEAD at like
+/// [`get_info_from_bundle`], resolved with `config`.
+///
+/// With [`GitflowConfig::offline`], `git` is only allowed local transports, so a path that is
+/// really a URL fails instead of being cloned over the network.
+pub fn get_info_from_bundle_with_config(
+    path: &Path,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let dir = TempDir::new()?;
+    let repo = dir.0.join("repo.git");
+    let mut command = Command::new("git");
+    if config.offline {
+        // Only local transports, so a URL given as the bundle path is refused instead of fetched
+        command.args([
+            "-c",
+            "protocol.allow=never",
+            "-c",
+            "protocol.file.allow=always",
+        ]);
+    }
+    let output = command
+        .arg("clone")
+        .arg("--quiet")
+        .arg("--bare")
+        .arg(path)
+        .arg(&repo)
+        .output()
+        .map_err(GitflowError::Io)?;
+    if !output.status.success() {
+        return Err(GitflowError::CommandFailed {
+            command: format!("git clone --bare {}", path.display()),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    get_info_with_config(&repo, config)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util::TestRepo, VersionInfo};
+
+    #[test]
+    fn info_from_bundle() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.1.0");
+        repo.commit("fix");
+
+        let bundle = repo.dir.path().join("repo.bundle");
+        let status = Command::new("git")
+            .arg("bundle")
+            .arg("create")
+            .arg(&bundle)
+            .arg("--all")
+            .current_dir(repo.dir.path())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+
+        let info = get_info_from_bundle(&bundle).unwrap();
+        assert_eq!(info.branch_name, "release/v1.1.0");
+        assert!(matches!(info.version, VersionInfo::Alpha(_)));
+        assert_eq!(info.version.get_semver().unwrap(), "v1.1.0-rc.1");
+        assert_eq!(info.build_number, 3);
+        assert_eq!(
+            info.commit_hash,
+            repo.repo.head().unwrap().target().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn missing_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = get_info_from_bundle(&dir.path().join("missing.bundle")).unwrap_err();
+        assert!(matches!(err, GitflowError::CommandFailed { .. }));
+    }
+
+    #[test]
+    fn offline_bundle() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let bundle = repo.dir.path().join("repo.bundle");
+        let status = Command::new("git")
+            .args(["bundle", "create"])
+            .arg(&bundle)
+            .arg("--all")
+            .current_dir(repo.dir.path())
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success());
+
+        let config = GitflowConfig {
+            offline: true,
+            ..Default::default()
+        };
+        assert!(repo.repo.remotes().unwrap().is_empty());
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+        let info = get_info_from_bundle_with_config(&bundle, &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+
+        let url = Path::new("https://example.com/repo.bundle");
+        let err = get_info_from_bundle_with_config(url, &config).unwrap_err();
+        match err {
+            GitflowError::CommandFailed { stderr, .. } => assert!(stderr.contains("not allowed")),
+            err => panic!("unexpected error {}", err),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! Source of the current time, injectable so time dependent results can be tested
+
+use crate::{short_hash, GitflowInfo};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Provides the current time as seconds since the Unix epoch
+pub trait Clock {
+    fn now(&self) -> i64;
+}
+
+/// The system's wall clock
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(before) => -(before.duration().as_secs() as i64),
+        }
+    }
+}
+
+/// A clock stopped at a fixed number of seconds since the Unix epoch
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub struct FixedClock(pub i64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Converts seconds since the Unix epoch to the UTC calendar date as year, month, and day
+pub(crate) fn utc_date(seconds: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's days_from_civil inverse, with eras of 400 years starting in March
+    let days = seconds.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = if month < 10 { month + 3 } else { month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+impl GitflowInfo {
+    /// Seconds between the committer time of the commit and now. Negative if the commit is dated
+    /// in the future, as happens with skewed clocks.
+    pub fn commit_age_seconds(&self) -> i64 {
+        self.commit_age_seconds_at(&SystemClock)
+    }
+
+    /// Like [`GitflowInfo::commit_age_seconds`], measured against `clock`
+    pub fn commit_age_seconds_at(&self, clock: &impl Clock) -> i64 {
+        clock.now() - self.commit_time
+    }
+
+    /// Returns a warning if the build is based on a commit older than `max_age`, which usually
+    /// means the checkout is stale. With the `log` feature the warning is also logged.
+    pub fn stale_warning(&self, max_age: Duration, clock: &impl Clock) -> Option<String> {
+        let age = self.commit_age_seconds_at(clock);
+        if age <= max_age.as_secs() as i64 {
+            return None;
+        }
+        let warning = format!(
+            "Building commit {} which is {} days old",
+            short_hash(&self.commit_hash),
+            age / 86_400
+        );
+        #[cfg(feature = "log")]
+        log::warn!("{}", warning);
+        Some(warning)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util, VersionInfo};
+
+    #[test]
+    fn utc_dates() {
+        assert_eq!(utc_date(0), (1970, 1, 1));
+        assert_eq!(utc_date(-1), (1969, 12, 31));
+        assert_eq!(utc_date(951_782_400), (2000, 2, 29));
+        assert_eq!(utc_date(1_715_731_199), (2024, 5, 14));
+        assert_eq!(utc_date(1_715_731_200), (2024, 5, 15));
+    }
+
+    #[test]
+    fn age_against_fixed_clock() {
+        let info = test_util::info(VersionInfo::Development, 1);
+        let clock = FixedClock(info.commit_time + 3 * 86_400);
+        assert_eq!(info.commit_age_seconds_at(&clock), 3 * 86_400);
+        assert_eq!(
+            info.stale_warning(Duration::from_secs(7 * 86_400), &clock),
+            None
+        );
+        assert_eq!(
+            info.stale_warning(Duration::from_secs(86_400), &clock)
+                .unwrap(),
+            "Building commit 0123456 which is 3 days old"
+        );
+        assert!(info.commit_age_seconds() > 0);
+    }
+}
@@ -0,0 +1,131 @@
+//! ANSI colored version banners for terminal output
+
+use crate::{short_hash, Channel, GitflowInfo, VersionInfo};
+
+/// Terminal foreground color
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Gray,
+}
+
+impl Color {
+    /// ANSI escape sequence that switches the foreground to this color
+    pub fn ansi_code(&self) -> &'static str {
+        match &self {
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Blue => "\x1b[34m",
+            Color::Magenta => "\x1b[35m",
+            Color::Cyan => "\x1b[36m",
+            Color::White => "\x1b[37m",
+            Color::Gray => "\x1b[90m",
+        }
+    }
+}
+
+/// ANSI escape sequence that resets all colors
+const RESET: &str = "\x1b[0m";
+
+/// Colors used for each channel when rendering banners
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub struct Palette {
+    pub production: Color,
+    pub alpha: Color,
+    pub development: Color,
+    pub local: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            production: Color::Green,
+            alpha: Color::Yellow,
+            development: Color::Blue,
+            local: Color::Gray,
+        }
+    }
+}
+
+impl Palette {
+    pub fn color(&self, channel: Channel) -> Color {
+        match channel {
+            Channel::Production => self.production,
+            Channel::Alpha => self.alpha,
+            Channel::Development => self.development,
+            Channel::Local => self.local,
+        }
+    }
+}
+
+impl VersionInfo {
+    /// Color of this version's channel in the default [`Palette`]
+    pub fn banner_color(&self) -> Color {
+        Palette::default().color(self.channel())
+    }
+}
+
+impl GitflowInfo {
+    /// Renders a one line banner describing this build, colored by channel using the default
+    /// [`Palette`]
+    pub fn colored_banner(&self) -> String {
+        self.colored_banner_with(&Palette::default())
+    }
+
+    /// Renders a one line banner describing this build, colored by channel using `palette`
+    pub fn colored_banner_with(&self, palette: &Palette) -> String {
+        format!(
+            "{}{} ({} {}, build {}){}",
+            palette.color(self.version.channel()).ansi_code(),
+            self.version,
+            self.branch_name,
+            short_hash(&self.commit_hash),
+            self.build_number,
+            RESET
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util, SemverBase};
+
+    fn info() -> GitflowInfo {
+        test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            42,
+        )
+    }
+
+    #[test]
+    fn banner_colors() {
+        assert_eq!(info().version.banner_color(), Color::Green);
+        assert_eq!(VersionInfo::Development.banner_color(), Color::Blue);
+        assert_eq!(VersionInfo::Local.banner_color(), Color::Gray);
+        assert_eq!(
+            info().colored_banner(),
+            "\x1b[32mProd: v1.2.3 (main 0123456, build 42)\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn banner_palette_override() {
+        let palette = Palette {
+            production: Color::Magenta,
+            ..Default::default()
+        };
+        assert!(info().colored_banner_with(&palette).starts_with("\x1b[35m"));
+    }
+}
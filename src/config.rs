@@ -0,0 +1,343 @@
+//! Options controlling how the gitflow version is determined
+
+use crate::{parse_semver, Channel, NoteConflict, RevocationMarker, SemverBase, VersionInfo};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What to do when a release branch's version is not greater than the latest production release
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum DowngradeCheck {
+    /// Don't compare release branches against existing releases
+    Ignore,
+
+    /// Log a warning through the `log` crate (requires the `log` feature)
+    Warn,
+
+    /// Fail with [`GitflowError::ReleaseDowngrade`](crate::GitflowError::ReleaseDowngrade)
+    Error,
+}
+
+/// Which repository versions a path in the working tree of a submodule
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum SubmoduleContext {
+    /// The submodule's own repository, found through the `.git` file pointing into the
+    /// superproject's `.git/modules/<name>`
+    Submodule,
+
+    /// The superproject containing the submodule
+    Superproject,
+}
+
+/// How [`GitflowInfo::dev_version`](crate::GitflowInfo::dev_version) is written
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum DevVersionScheme {
+    /// The release develop works towards, e.g. `v1.3.0-dev.42`. See
+    /// [`VersionInfo::to_dev_version`](crate::VersionInfo::to_dev_version).
+    Semver,
+
+    /// The UTC date of the commit's committer time, e.g. `2024.05.14-dev.42`. The date comes from
+    /// the commit rather than the time of the build, so rebuilding a commit gives the same
+    /// version. Note the zero padded month and day aren't valid semver.
+    Calendar,
+}
+
+/// Predicate deciding which tags are considered during version resolution, given the short name
+/// of each tag (e.g. `v1.2.3`)
+#[derive(Clone)]
+pub struct TagFilter(Arc<dyn Fn(&str) -> bool + Send + Sync>);
+
+impl TagFilter {
+    pub fn new(filter: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(filter))
+    }
+
+    pub fn accepts(&self, name: &str) -> bool {
+        (self.0)(name)
+    }
+}
+
+impl std::fmt::Debug for TagFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TagFilter(..)")
+    }
+}
+
+/// Explicit channel, and optionally base version, for a branch whose name doesn't follow the
+/// gitflow patterns. See [`GitflowConfig::branch_overrides`].
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub struct ChannelOverride {
+    pub channel: Channel,
+
+    /// Base version of the branch. For production, this pins the version of every commit on the
+    /// branch instead of reading production tags. For alpha, this is the version release
+    /// candidates are numbered for, otherwise the highest version with release candidates is
+    /// continued. Ignored for development and local branches.
+    pub base: Option<SemverBase>,
+}
+
+/// Marketing name of a range of releases, see [`GitflowConfig::codenames`]
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct Codename {
+    /// Lowest base version with this name
+    pub start: SemverBase,
+
+    /// Lowest base version after `start` without this name, so `v22.0.0` to `v23.0.0` names
+    /// every `v22.x` release
+    pub end: SemverBase,
+
+    pub name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitflowConfig {
+    /// Check that the base version of a release branch is greater than the highest production
+    /// tag. Creating `release/v1.2.0` after `v1.3.0` was released is almost always a mistake.
+    pub release_downgrade: DowngradeCheck,
+
+    /// Record the commit each submodule is pinned to in
+    /// [`GitflowInfo::submodule_pins`](crate::GitflowInfo::submodule_pins)
+    pub submodule_pins: bool,
+
+    /// Only count annotated tags as version tags. By default lightweight tags are release markers
+    /// too, set this when releases are signed and a bare lightweight tag shouldn't be trusted.
+    pub require_annotated_tags: bool,
+
+    /// Prefix of the temporary refs merge queues build on, such as
+    /// `gh-readonly-queue/main/pr-123-<sha>`. Refs with this prefix are classified as the branch
+    /// they will be merged into, taken from the ref name between the prefix and the final
+    /// `/pr-`. Set to `None` to classify them like any other branch.
+    pub merge_queue_prefix: Option<String>,
+
+    /// Only tags accepted by this filter count as version tags, which keeps noise such as CI or
+    /// nightly tags out of version resolution. By default every tag whose name parses as a
+    /// version is considered.
+    pub tag_filter: Option<TagFilter>,
+
+    /// Prefix of release train branches and tags, such as `train/`. Setting this enables the
+    /// release train model alongside regular gitflow.
+    ///
+    /// A train is named by a train number or a date, e.g. `train/42` or `train/2024.05`, which
+    /// gives the base version of the train (`v42.0.0` and `v24.5.0`, four digit years count from
+    /// 2000). Commits on a train branch are alpha versions whose release candidate number is their
+    /// position in the train, the number of commits on the train since it left develop. A tag with
+    /// the train's name marks the commit the train shipped as, making it a production version.
+    pub release_train_prefix: Option<String>,
+
+    /// Version to report when the version can't be determined at all, for example outside a
+    /// repository or on a production branch without tags. When set,
+    /// [`get_info_with_config`](crate::get_info_with_config) never fails and instead returns an
+    /// info with this version and an empty branch and commit.
+    ///
+    /// This masks every error, including misconfigured repositories that would otherwise be
+    /// caught, so only set it deliberately, such as in build scripts that must succeed when built
+    /// from a source tarball. With the `log` feature the masked error is logged as a warning.
+    pub fallback_version: Option<VersionInfo>,
+
+    /// Channels of specific branches, keyed by the full branch name, e.g. `legacy-main` as a
+    /// production branch with base `v1.0.0`. Entries take precedence over the branch patterns
+    /// and every other config option, but not over the channel overrides of
+    /// [`CHANNEL_ENV_VAR`](crate::CHANNEL_ENV_VAR) and [`CHANNEL_FILE`](crate::CHANNEL_FILE).
+    /// They only affect the version of the branch itself, other lookups such as finding the
+    /// develop branch still go by name.
+    pub branch_overrides: HashMap<String, ChannelOverride>,
+
+    /// In GitHub Actions builds of a version tag, take the version from `GITHUB_REF` and the
+    /// commit from `GITHUB_SHA` without opening the repository, so tag builds don't need a full
+    /// checkout with history. Only applies when `GITHUB_ACTIONS` is `true` and both are well
+    /// formed, e.g. `refs/tags/v1.2.3` and a full commit hash. The branch and tree hash of these
+    /// builds are empty and the build number is 0.
+    pub github_ref_fast_path: bool,
+
+    /// Count the files in the tree of the commit into
+    /// [`GitflowInfo::tree_file_count`](crate::GitflowInfo::tree_file_count). This walks the
+    /// whole tree, so it is off by default.
+    pub tree_file_count: bool,
+
+    /// File persisting a build counter, relative to the repository unless absolute, such as
+    /// `target/.gitflow-build-counter`. When set, each
+    /// [`get_info_with_config`](crate::get_info_with_config) increments the counter and reports
+    /// it in [`GitflowInfo::build_counter`](crate::GitflowInfo::build_counter), unless
+    /// [`GitflowConfig::dry_run`] is set, which reports the next value without storing it. Unlike
+    /// the build number, the counter survives rebases and increases with every local build.
+    ///
+    /// The file is locked while it is updated, so parallel builds sharing it each get a distinct
+    /// value, waiting on each other if needed. Builds on different machines don't share a counter.
+    pub build_counter_file: Option<PathBuf>,
+
+    /// Follow replace refs made with `git replace`, such as grafts that cut off old history, when
+    /// counting build numbers and finding the tags in the history of a commit. This matches the
+    /// history `git log` shows, while by default the history stored in the commits is used, as
+    /// libgit2 doesn't apply replacements. Other walks, such as release windows, always use the
+    /// stored history.
+    pub follow_replace_refs: bool,
+
+    /// Scheme of the version given to development builds
+    pub dev_version_scheme: DevVersionScheme,
+
+    /// Number development builds by integrations rather than commits when develop merges branches
+    /// with merge commits, see [`IntegrationStyle`](crate::IntegrationStyle). Only develop's first
+    /// parent history is counted, so merging a branch adds one to the build number however many
+    /// commits it has, as squash merging does. Squash merged histories are counted as usual.
+    pub count_integrations: bool,
+
+    /// Packages of a monorepo for [`manifest_versions`](crate::manifest_versions), mapping the
+    /// path of each package to the prefix of its tags, e.g. `packages/core` to `core-` for tags
+    /// like `core-v1.2.0`. The rest of the tag name after the prefix is parsed as a version.
+    pub packages: HashMap<String, String>,
+
+    /// Repository versioned when the path given to
+    /// [`get_info_with_config`](crate::get_info_with_config) or
+    /// [`Gitflow::open_with_config`](crate::Gitflow::open_with_config) is a submodule checkout
+    pub submodule_context: SubmoduleContext,
+
+    /// Fail with [`GitflowError::Ambiguous`](crate::GitflowError::Ambiguous) instead of guessing
+    /// when the version isn't certain, for release pipelines that must be reproducible. This
+    /// rejects a commit with several release tags, or several release candidate tags of its
+    /// release branch, and a production commit without a release tag, which is otherwise the
+    /// latest release in its history. It also disables
+    /// [`fallback_version`](GitflowConfig::fallback_version).
+    pub strict: bool,
+
+    /// Release names to fill [`GitflowInfo::codename`](crate::GitflowInfo::codename) from, such
+    /// as "Jammy" for `v22.x`. The first range containing the base version names it.
+    pub codenames: Vec<Codename>,
+
+    /// Number of hex digits commit, tree, and submodule pin hashes are abbreviated to in
+    /// [`GitflowInfo`](crate::GitflowInfo) and errors, at least 4. `None` keeps the full 40. An
+    /// abbreviated hash can become ambiguous as the repository grows, so prefer full hashes for
+    /// anything stored.
+    pub hash_length: Option<usize>,
+
+    /// Version a `path` that isn't a repository as a GitHub source tarball. GitHub names the
+    /// directory of a tarball `<owner>-<repo>-<sha>`, so the nearest of `path` and its parents
+    /// whose name ends in `-` and 7 to 40 lowercase hex digits is taken as the tarball root, and
+    /// those digits as the commit. The version comes from the
+    /// [`VERSION_ENV_VAR`](crate::VERSION_ENV_VAR) environment variable, or else the
+    /// [`VERSION_FILE`](crate::VERSION_FILE) at the tarball root, and must be a production or
+    /// release candidate version. The directory name is only a heuristic, a directory that
+    /// happens to end in hex digits is taken for a tarball too, hence this is off by default.
+    pub tarball_fallback: bool,
+
+    /// Give up on [`get_info_with_config`](crate::get_info_with_config) after this long, failing
+    /// with [`GitflowError::Timeout`](crate::GitflowError::Timeout), or giving the
+    /// [`GitflowConfig::fallback_version`] if one is set, so a pathological repository can't stall
+    /// a build script indefinitely. Resolution runs on a separate thread that is abandoned rather
+    /// than interrupted when the deadline passes, as libgit2 calls can't be cancelled, so the
+    /// deadline bounds how long the caller waits but not how long the work runs.
+    pub timeout: Option<Duration>,
+
+    /// Guarantee that nothing touches the network, for air gapped builds. Versions are always
+    /// resolved from local refs only, remote tracking branches as of the last fetch, whether or
+    /// not this is set: [`unpushed_commits`](crate::unpushed_commits) and
+    /// [`target_branch_version`](crate::target_branch_version) compare against the remote
+    /// tracking refs already fetched, giving `None` without them, and no remote is ever contacted
+    /// to find its default branch. What this changes is the `git` command spawned by
+    /// [`get_info_from_bundle_with_config`](crate::get_info_from_bundle_with_config), which is
+    /// restricted to local transports so a URL can't make it fetch.
+    pub offline: bool,
+
+    /// Skip operations that write to the repository, such as
+    /// [`write_version_note`](crate::write_version_note), logging what would have been written
+    /// with the `log` feature instead
+    pub dry_run: bool,
+
+    /// Notes ref [`write_version_note`](crate::write_version_note) records versions under
+    pub notes_ref: String,
+
+    /// What [`write_version_note`](crate::write_version_note) does with an existing note
+    pub note_conflict: NoteConflict,
+
+    /// How [`is_revoked`](crate::is_revoked) recognizes revoked releases
+    pub revocation_marker: RevocationMarker,
+
+    /// Pattern extracting versions from tag and branch names, replacing the built in `vX.Y.Z`
+    /// and `vX.Y.Z-rc.W` grammar for teams with their own naming. The pattern must have the
+    /// named capture groups `major`, `minor`, and `patch`, and may have `rc`. A name is a release
+    /// candidate if `rc` participates in the match, otherwise a release. Components that don't
+    /// fit the version fields make the name unversioned.
+    ///
+    /// The pattern is matched against tag names and the version part of release and hotfix
+    /// branch names (after `release/` and `hotfix/`). Anchor it with `^` and `$` unless partial
+    /// matches are intended. For tags like `rel_1_2_3` and `rel_1_2_3_rc4`:
+    ///
+    /// ```text
+    /// ^rel_(?P<major>\d+)_(?P<minor>\d+)_(?P<patch>\d+)(_rc(?P<rc>\d+))?$
+    /// ```
+    ///
+    /// Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub tag_regex: Option<regex::Regex>,
+}
+
+impl Default for GitflowConfig {
+    fn default() -> Self {
+        Self {
+            release_downgrade: DowngradeCheck::Ignore,
+            submodule_pins: false,
+            require_annotated_tags: false,
+            merge_queue_prefix: Some("gh-readonly-queue/".to_owned()),
+            tag_filter: None,
+            release_train_prefix: None,
+            fallback_version: None,
+            branch_overrides: HashMap::new(),
+            github_ref_fast_path: false,
+            tree_file_count: false,
+            build_counter_file: None,
+            follow_replace_refs: false,
+            dev_version_scheme: DevVersionScheme::Semver,
+            count_integrations: false,
+            packages: HashMap::new(),
+            submodule_context: SubmoduleContext::Submodule,
+            strict: false,
+            codenames: Vec::new(),
+            hash_length: None,
+            tarball_fallback: false,
+            timeout: None,
+            offline: false,
+            dry_run: false,
+            notes_ref: "refs/notes/gitflow".to_owned(),
+            note_conflict: NoteConflict::Overwrite,
+            revocation_marker: RevocationMarker::default(),
+            #[cfg(feature = "regex")]
+            tag_regex: None,
+        }
+    }
+}
+
+impl GitflowConfig {
+    /// Parses the version in a tag or branch name with [`GitflowConfig::tag_regex`] if it is set,
+    /// or [`parse_semver`] otherwise
+    pub(crate) fn parse_version(&self, name: &str) -> Option<VersionInfo> {
+        #[cfg(feature = "regex")]
+        if let Some(regex) = &self.tag_regex {
+            let captures = regex.captures(name)?;
+            let component = |group: &str| captures.name(group).map(|m| m.as_str().parse().ok());
+            let base = SemverBase {
+                major: component("major")??,
+                minor: component("minor")??,
+                patch: component("patch")??,
+            };
+            return Some(match component("rc") {
+                Some(rc) => VersionInfo::Alpha(crate::SemverRC { base, rc: rc? }),
+                None => VersionInfo::Production(base),
+            });
+        }
+        parse_semver(name).ok()
+    }
+
+    /// Looks up the codename of a production or alpha `version` in [`GitflowConfig::codenames`]
+    pub(crate) fn codename(&self, version: &VersionInfo) -> Option<String> {
+        let base = match version {
+            VersionInfo::Production(base) | VersionInfo::Alpha(crate::SemverRC { base, .. }) => {
+                base
+            }
+            _ => return None,
+        };
+        self.codenames
+            .iter()
+            .find(|codename| (codename.start..codename.end).contains(base))
+            .map(|codename| codename.name.clone())
+    }
+}
@@ -0,0 +1,52 @@
+//! Build counter persisted in a file, independent of the git history
+
+use crate::GitflowError;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Increments the counter stored in the file at `path` and returns the new value. A missing or
+/// empty file counts as 0, so the first build is 1. With `dry_run` the next value is returned
+/// without being stored.
+///
+/// The file is held under an exclusive lock from reading the old value until the new one is
+/// written, so parallel builds sharing the file block on each other and each get a distinct value.
+pub(crate) fn increment(path: &Path, dry_run: bool) -> Result<u64, GitflowError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(GitflowError::Io)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(GitflowError::Io)?;
+    file.lock().map_err(GitflowError::Io)?;
+    let counter = read(&mut file)? + 1;
+    if !dry_run {
+        file.set_len(0).map_err(GitflowError::Io)?;
+        file.seek(SeekFrom::Start(0)).map_err(GitflowError::Io)?;
+        writeln!(file, "{}", counter).map_err(GitflowError::Io)?;
+        file.sync_all().map_err(GitflowError::Io)?;
+    }
+    Ok(counter)
+}
+
+/// Reads the counter stored in `file`. Contents that aren't a number are an error rather than a
+/// reset, so a corrupted file never makes the counter go backwards.
+fn read(file: &mut File) -> Result<u64, GitflowError> {
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(GitflowError::Io)?;
+    let contents = contents.trim();
+    if contents.is_empty() {
+        return Ok(0);
+    }
+    contents.parse().map_err(|_| {
+        GitflowError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("build counter {:?} is not a number", contents),
+        ))
+    })
+}
@@ -0,0 +1,162 @@
+//! Errors produced while determining the gitflow version of a repository
+
+use crate::SemverBase;
+use std::fmt::Display;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum GitflowError {
+    /// Error from libgit2 while reading the repository
+    Git(git2::Error),
+
+    /// Error reading a file in the working tree
+    Io(std::io::Error),
+
+    /// External command exited unsuccessfully
+    CommandFailed { command: String, stderr: String },
+
+    /// HEAD commit isn't on any local branch
+    NoBranch { commit: String },
+
+    /// HEAD commit is on more than one local branch
+    TooManyBranches { branches: Vec<String> },
+
+    /// Production branch has no production tag to take its version from
+    NoProductionTag { branch: String },
+
+    /// Channel name isn't one of the known channels
+    UnknownChannel { channel: String },
+
+    /// Alpha channel was requested but there are no release candidate tags to continue from
+    NoReleaseCandidate,
+
+    /// Release branch version is not greater than the latest production release
+    ReleaseDowngrade {
+        branch: String,
+        release: SemverBase,
+        latest: SemverBase,
+    },
+
+    /// HEAD isn't on a release branch
+    NotReleaseBranch { branch: String },
+
+    /// Branch shares no history with develop, or there is no develop branch
+    NoBranchPoint { branch: String },
+
+    /// Proposed tag isn't a production or release candidate version
+    InvalidTag { tag: String },
+
+    /// Proposed tag already exists
+    TagExists { tag: String },
+
+    /// Proposed tag isn't greater than an existing version it must follow
+    TagNotGreater { tag: String, existing: String },
+
+    /// Proposed tag isn't a version of the release line of the current branch
+    WrongVersionLine { tag: String, branch: String },
+
+    /// Release candidate tags of a version skip numbers, listed in `missing`. See
+    /// [`validate_rc_sequence`](crate::validate_rc_sequence).
+    RcGap { base: SemverBase, missing: Vec<u8> },
+
+    /// Commit could be more than one version, each listed in `interpretations`. Only returned with
+    /// [`GitflowConfig::strict`](crate::GitflowConfig::strict), which refuses to guess.
+    Ambiguous {
+        commit: String,
+        interpretations: Vec<String>,
+    },
+
+    /// Version resolution took longer than the configured timeout, see
+    /// [`GitflowConfig::timeout`](crate::GitflowConfig::timeout)
+    Timeout { timeout: Duration },
+}
+
+impl Display for GitflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            GitflowError::Git(err) => write!(f, "git error: {}", err),
+            GitflowError::Io(err) => write!(f, "io error: {}", err),
+            GitflowError::CommandFailed { command, stderr } => {
+                write!(f, "{} failed: {}", command, stderr)
+            }
+            GitflowError::NoBranch { commit } => write!(f, "Commit {} on no branch", commit),
+            GitflowError::TooManyBranches { branches } => {
+                write!(f, "Commit on too many branches: {}", branches.join(", "))
+            }
+            GitflowError::NoProductionTag { branch } => {
+                write!(f, "No production tag found for branch {}", branch)
+            }
+            GitflowError::UnknownChannel { channel } => write!(f, "Unknown channel: {}", channel),
+            GitflowError::NoReleaseCandidate => {
+                write!(
+                    f,
+                    "No release candidate tags to derive an alpha version from"
+                )
+            }
+            GitflowError::ReleaseDowngrade {
+                branch,
+                release,
+                latest,
+            } => write!(
+                f,
+                "Release branch {} has version {} which is not greater than the latest release {}",
+                branch, release, latest
+            ),
+            GitflowError::NotReleaseBranch { branch } => {
+                write!(f, "Branch {} is not a release branch", branch)
+            }
+            GitflowError::NoBranchPoint { branch } => {
+                write!(f, "Branch {} did not branch from develop", branch)
+            }
+            GitflowError::InvalidTag { tag } => write!(f, "Tag {} is not a version", tag),
+            GitflowError::TagExists { tag } => write!(f, "Tag {} already exists", tag),
+            GitflowError::TagNotGreater { tag, existing } => {
+                write!(
+                    f,
+                    "Tag {} is not greater than existing version {}",
+                    tag, existing
+                )
+            }
+            GitflowError::WrongVersionLine { tag, branch } => {
+                write!(f, "Tag {} is not a version of branch {}", tag, branch)
+            }
+            GitflowError::RcGap { base, missing } => {
+                let missing: Vec<_> = missing.iter().map(|rc| format!("rc.{}", rc)).collect();
+                write!(
+                    f,
+                    "Release candidates of {} are missing {}",
+                    base,
+                    missing.join(", ")
+                )
+            }
+            GitflowError::Ambiguous {
+                commit,
+                interpretations,
+            } => write!(
+                f,
+                "Version of commit {} is ambiguous, it could be {}",
+                commit,
+                interpretations.join(" or ")
+            ),
+            GitflowError::Timeout { timeout } => {
+                write!(f, "Version resolution timed out after {:?}", timeout)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GitflowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self {
+            GitflowError::Git(err) => Some(err),
+            GitflowError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<git2::Error> for GitflowError {
+    fn from(err: git2::Error) -> Self {
+        GitflowError::Git(err)
+    }
+}
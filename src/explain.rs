@@ -0,0 +1,143 @@
+//! Step by step account of how the version of HEAD is decided
+
+use crate::{
+    classify_branch, format_hash, get_info_for_repo, read_channel_file, GitflowConfig,
+    GitflowError, GitflowInfo, TagIndex, VersionInfo, CHANNEL_ENV_VAR, CHANNEL_FILE,
+};
+use git2::Repository;
+
+/// Record of the decisions made while versioning HEAD, for debugging an unexpected version
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Explanation {
+    /// Human readable decision steps, in the order they were made
+    pub steps: Vec<String>,
+    /// The info the steps arrive at
+    pub info: GitflowInfo,
+}
+
+/// Versions HEAD of `repo` the way [`get_info_with_config`](crate::get_info_with_config) does,
+/// recording each decision along the way
+pub fn explain(repo: &Repository, config: &GitflowConfig) -> Result<Explanation, GitflowError> {
+    explain_with_sink(repo, config, |_| {})
+}
+
+/// Like [`explain`], additionally streaming each step to `sink` as soon as it is decided, so tools
+/// can show progress on slow repositories. Steps reach `sink` one at a time in the order of
+/// [`Explanation::steps`], each before any later step is worked out. If versioning fails, the
+/// steps made before the failure have still been streamed.
+pub fn explain_with_sink(
+    repo: &Repository,
+    config: &GitflowConfig,
+    sink: impl Fn(&str),
+) -> Result<Explanation, GitflowError> {
+    let mut steps = Vec::new();
+    let mut step = |step: String| {
+        sink(&step);
+        steps.push(step);
+    };
+
+    let head = repo.head()?;
+    let commit = head.peel_to_commit()?.id();
+    step(format!("HEAD is commit {}", format_hash(commit, config)));
+    let branch = if head.is_branch() {
+        head.shorthand().unwrap_or_default().to_owned()
+    } else {
+        String::new()
+    };
+    if branch.is_empty() {
+        step("HEAD is detached".to_owned());
+    } else {
+        step(format!("HEAD is on branch {}", branch));
+        match config.branch_overrides.get(&branch) {
+            Some(entry) => step(format!(
+                "Branch {} is configured as the {} channel",
+                branch, entry.channel
+            )),
+            None => step(format!(
+                "Branch {} is classified as {:?}",
+                branch,
+                classify_branch(&branch, config)
+            )),
+        }
+    }
+
+    if let Ok(channel) = std::env::var(CHANNEL_ENV_VAR) {
+        step(format!(
+            "{} overrides the channel to {}",
+            CHANNEL_ENV_VAR,
+            channel.trim()
+        ));
+    } else if let Some(channel) = repo.workdir().map(read_channel_file).transpose()?.flatten() {
+        step(format!(
+            "{} overrides the channel to {}",
+            CHANNEL_FILE,
+            channel.trim()
+        ));
+    }
+
+    let tags = TagIndex::new(repo, config)?;
+    let reachable = tags.reachable_from(repo, commit)?;
+    step(format!(
+        "Found {} version tags, {} in the history of HEAD",
+        tags.tags().len(),
+        reachable.len()
+    ));
+    let latest = reachable
+        .iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some(base),
+            _ => None,
+        })
+        .max();
+    if let Some(latest) = latest {
+        step(format!(
+            "Latest release in the history of HEAD is {}",
+            latest
+        ));
+    }
+
+    let info = get_info_for_repo(repo, config)?;
+    step(format!(
+        "Resolved {} with build number {}",
+        info.version, info.build_number
+    ));
+    if let Some(dev_version) = &info.dev_version {
+        step(format!("Development version is {}", dev_version));
+    }
+    Ok(Explanation { steps, info })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+    use std::cell::RefCell;
+
+    #[test]
+    fn streamed_steps() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        let head = repo.commit("feature");
+
+        let streamed = RefCell::new(Vec::new());
+        let explanation = explain_with_sink(&repo.repo, &GitflowConfig::default(), |step| {
+            streamed.borrow_mut().push(step.to_owned())
+        })
+        .unwrap();
+        assert_eq!(streamed.into_inner(), explanation.steps);
+        assert_eq!(explanation.steps[0], format!("HEAD is commit {}", head));
+        assert!(explanation
+            .steps
+            .contains(&"Branch develop is classified as Develop".to_owned()));
+        assert!(explanation
+            .steps
+            .contains(&"Found 1 version tags, 1 in the history of HEAD".to_owned()));
+        assert_eq!(
+            explanation.steps.last().unwrap(),
+            "Development version is v1.1.0-dev.2"
+        );
+        assert_eq!(explanation.info.version, VersionInfo::Development);
+    }
+}
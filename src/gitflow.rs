@@ -0,0 +1,132 @@
+//! Handle bundling an open repository with its configuration
+
+use crate::{
+    get_info_with_repo, GitflowConfig, GitflowError, GitflowInfo, SemverBase, TagIndex, VersionTag,
+};
+use git2::Repository;
+use std::path::Path;
+
+/// An open repository together with the configuration used to version it, for library consumers
+/// that make several queries against the same repository.
+///
+/// `Gitflow` owns its [`Repository`] and [`GitflowConfig`], so it has no lifetime parameters and
+/// can be stored or moved freely. Queries borrow it immutably and return owned values, so results
+/// don't keep it borrowed. Each query reads the refs afresh, so tags and branches created after
+/// the repository was opened are seen. Like [`Repository`], it is [`Send`] but not [`Sync`].
+///
+/// Every method is a thin wrapper over the equivalent free function, which remain available for
+/// one off queries.
+pub struct Gitflow {
+    repo: Repository,
+    config: GitflowConfig,
+}
+
+impl Gitflow {
+    /// Opens the repository at `path` with the default configuration
+    pub fn open(path: &Path) -> Result<Self, GitflowError> {
+        Self::open_with_config(path, GitflowConfig::default())
+    }
+
+    /// Opens the repository at `path` with `config`
+    pub fn open_with_config(path: &Path, config: GitflowConfig) -> Result<Self, GitflowError> {
+        Ok(Self::new(crate::open_repository(path, &config)?, config))
+    }
+
+    /// Wraps an already opened repository
+    pub fn new(repo: Repository, config: GitflowConfig) -> Self {
+        Self { repo, config }
+    }
+
+    pub fn repo(&self) -> &Repository {
+        &self.repo
+    }
+
+    pub fn config(&self) -> &GitflowConfig {
+        &self.config
+    }
+
+    /// Determines the version of HEAD, like [`get_info_with_config`](crate::get_info_with_config)
+    /// including its fallback version, strict mode, timeout, build counter, and GitHub fast path.
+    /// The build counter file is relative to the working tree.
+    pub fn info(&self) -> Result<GitflowInfo, GitflowError> {
+        get_info_with_repo(&self.repo, &self.config)
+    }
+
+    /// Lists the version tags in the repository, as indexed by [`TagIndex`]
+    pub fn list_tags(&self) -> Result<Vec<VersionTag>, GitflowError> {
+        Ok(TagIndex::new(&self.repo, &self.config)?.tags().to_vec())
+    }
+
+    /// Returns the highest production version that has been tagged, if any
+    pub fn latest_production(&self) -> Result<Option<SemverBase>, GitflowError> {
+        Ok(TagIndex::new(&self.repo, &self.config)?.latest_production())
+    }
+}
+
+impl std::fmt::Debug for Gitflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Gitflow")
+            .field("path", &self.repo.path())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util::TestRepo, VersionInfo};
+
+    #[test]
+    fn queries_reuse_open_repo() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let gitflow = Gitflow::open(repo.dir.path()).unwrap();
+        let base = SemverBase {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        };
+        assert_eq!(gitflow.latest_production().unwrap(), Some(base));
+        assert_eq!(
+            gitflow.info().unwrap().version,
+            VersionInfo::Production(base)
+        );
+
+        // Refs created after opening are seen by later queries
+        repo.commit("fix");
+        repo.tag("v1.0.1");
+        let tags: Vec<String> = gitflow
+            .list_tags()
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+        assert_eq!(tags, ["v1.0.0", "v1.0.1"]);
+        assert_eq!(
+            gitflow.info().unwrap().version.get_semver().unwrap(),
+            "v1.0.1"
+        );
+    }
+
+    #[test]
+    fn info_falls_back() {
+        let repo = TestRepo::new();
+        let initial = repo.commit("initial");
+        repo.repo.set_head_detached(initial).unwrap();
+        let config = GitflowConfig {
+            fallback_version: Some(VersionInfo::Local),
+            ..Default::default()
+        };
+        let gitflow = Gitflow::open_with_config(repo.dir.path(), config.clone()).unwrap();
+        assert_eq!(gitflow.info().unwrap().version, VersionInfo::Local);
+
+        let strict = GitflowConfig {
+            strict: true,
+            ..config
+        };
+        let gitflow = Gitflow::open_with_config(repo.dir.path(), strict).unwrap();
+        assert!(matches!(gitflow.info(), Err(GitflowError::NoBranch { .. })));
+    }
+}
@@ -0,0 +1,87 @@
+//! Versions of GitHub Actions builds read from the environment
+
+use crate::{abbreviate_hash, parse_semver, GitflowConfig, GitflowInfo, VersionInfo};
+
+/// Reads the version of a GitHub Actions build triggered by pushing a version tag, straight from
+/// `GITHUB_REF` and `GITHUB_SHA` without opening the repository. Returns `None` unless
+/// `GITHUB_ACTIONS` is `true`, `GITHUB_REF` is a production or release candidate tag such as
+/// `refs/tags/v1.2.3`, and `GITHUB_SHA` is a full commit hash. The commit hash is abbreviated like
+/// any other, see [`GitflowConfig::hash_length`].
+pub(crate) fn github_tag_info(config: &GitflowConfig) -> Option<GitflowInfo> {
+    tag_info(
+        std::env::var("GITHUB_ACTIONS").ok()?.as_str(),
+        std::env::var("GITHUB_REF").ok()?.as_str(),
+        std::env::var("GITHUB_SHA").ok()?.as_str(),
+        config,
+    )
+}
+
+fn tag_info(
+    actions: &str,
+    github_ref: &str,
+    sha: &str,
+    config: &GitflowConfig,
+) -> Option<GitflowInfo> {
+    if actions != "true" || sha.len() != 40 || !sha.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let version = match parse_semver(github_ref.strip_prefix("refs/tags/")?).ok()? {
+        version @ (VersionInfo::Production(_) | VersionInfo::Alpha(_)) => version,
+        _ => return None,
+    };
+    let mut info = GitflowInfo::without_repo(version, 0);
+    info.commit_hash = abbreviate_hash(sha.to_ascii_lowercase(), config);
+    Some(info)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::get_info_with_config;
+
+    const SHA: &str = "0123456789abcdef0123456789abcdef01234567";
+
+    #[test]
+    fn well_formed_refs_only() {
+        let config = GitflowConfig::default();
+        let info = tag_info("true", "refs/tags/v1.2.3-rc.4", SHA, &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.2.3-rc.4");
+        assert_eq!(info.commit_hash, SHA);
+
+        assert!(tag_info("false", "refs/tags/v1.2.3", SHA, &config).is_none());
+        assert!(tag_info("true", "refs/heads/v1.2.3", SHA, &config).is_none());
+        assert!(tag_info("true", "refs/tags/nightly", SHA, &config).is_none());
+        assert!(tag_info("true", "refs/tags/v1.2.3", "0123456", &config).is_none());
+        let invalid = SHA.replace('0', "g");
+        assert!(tag_info("true", "refs/tags/v1.2.3", &invalid, &config).is_none());
+
+        let config = GitflowConfig {
+            hash_length: Some(12),
+            ..Default::default()
+        };
+        let info = tag_info("true", "refs/tags/v1.2.3", SHA, &config).unwrap();
+        assert_eq!(info.commit_hash, SHA[..12]);
+    }
+
+    #[test]
+    fn fast_path_from_env() {
+        // Not a repository, so this can only succeed without touching git
+        let dir = tempfile::tempdir().unwrap();
+        let config = GitflowConfig {
+            github_ref_fast_path: true,
+            ..Default::default()
+        };
+        std::env::set_var("GITHUB_ACTIONS", "true");
+        std::env::set_var("GITHUB_REF", "refs/tags/v1.2.3");
+        std::env::set_var("GITHUB_SHA", SHA);
+        let info = get_info_with_config(dir.path(), &config);
+        std::env::remove_var("GITHUB_ACTIONS");
+        std::env::remove_var("GITHUB_REF");
+        std::env::remove_var("GITHUB_SHA");
+
+        let info = info.unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.2.3");
+        assert_eq!(info.commit_hash, SHA);
+        assert!(get_info_with_config(dir.path(), &config).is_err());
+    }
+}
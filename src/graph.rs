@@ -0,0 +1,874 @@
+//! Relationships between HEAD and the gitflow branches in the commit graph
+
+use crate::{
+    classify_branch, format_hash, get_info_for_commit, get_info_for_repo, local_branches,
+    BranchClass, Channel, GitflowConfig, GitflowError, SemverBase, TagIndex, VersionInfo,
+};
+use git2::{Oid, Repository};
+use std::collections::{HashMap, HashSet};
+
+/// Where HEAD sits relative to the tip of develop
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum DevelopPosition {
+    /// HEAD is the latest commit on develop
+    Tip,
+
+    /// HEAD is an older commit on develop, this many commits behind the tip
+    Behind(usize),
+}
+
+/// Reads the replacements made with `git replace`, mapping each replaced commit to the commit
+/// that stands in for it
+pub(crate) fn replace_refs(repo: &Repository) -> Result<HashMap<Oid, Oid>, git2::Error> {
+    let mut replacements = HashMap::new();
+    for reference in repo.references_glob("refs/replace/*")? {
+        let reference = reference?;
+        let replaced = reference
+            .name()
+            .and_then(|name| name.strip_prefix("refs/replace/"))
+            .and_then(|id| Oid::from_str(id).ok());
+        if let (Some(replaced), Some(replacement)) = (replaced, reference.target()) {
+            replacements.insert(replaced, replacement);
+        }
+    }
+    Ok(replacements)
+}
+
+/// Collects the history of `start` the way `git log` shows it, with every commit that has a
+/// replacement swapped for it. libgit2's revwalk ignores replacements. Both the ids of replaced
+/// commits and of their replacements are included, as refs may point at either.
+pub(crate) fn replaced_history(
+    repo: &Repository,
+    start: Oid,
+    replacements: &HashMap<Oid, Oid>,
+) -> Result<HashSet<Oid>, git2::Error> {
+    let mut history = HashSet::new();
+    let mut pending = vec![start];
+    while let Some(oid) = pending.pop() {
+        if !history.insert(oid) {
+            continue;
+        }
+        let oid = match replacements.get(&oid) {
+            Some(replacement) => {
+                history.insert(*replacement);
+                *replacement
+            }
+            None => oid,
+        };
+        pending.extend(repo.find_commit(oid)?.parent_ids());
+    }
+    Ok(history)
+}
+
+/// Counts the commits in the history of `start`, following replace refs if
+/// [`GitflowConfig::follow_replace_refs`] is set
+pub(crate) fn count_history(
+    repo: &Repository,
+    start: Oid,
+    config: &GitflowConfig,
+) -> Result<usize, GitflowError> {
+    if config.follow_replace_refs {
+        let replacements = replace_refs(repo)?;
+        if !replacements.is_empty() {
+            let history = replaced_history(repo, start, &replacements)?;
+            // Replaced commits are shown as their replacement, so only count them once
+            let replaced = replacements
+                .iter()
+                .filter(|(replaced, replacement)| {
+                    replaced != replacement
+                        && history.contains(*replaced)
+                        && history.contains(*replacement)
+                })
+                .count();
+            return Ok(history.len() - replaced);
+        }
+    }
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(start)?;
+    Ok(revwalk.count())
+}
+
+/// Counts the commits on the first parent history of `start`, i.e. one for each integration into
+/// the branch and each commit made on it directly
+pub(crate) fn count_first_parent_history(
+    repo: &Repository,
+    start: Oid,
+) -> Result<usize, GitflowError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(start)?;
+    revwalk.simplify_first_parent()?;
+    Ok(revwalk.count())
+}
+
+/// Returns the tip of the first local branch of class `class`, if there is one
+fn branch_tip(
+    repo: &Repository,
+    config: &GitflowConfig,
+    class: BranchClass,
+) -> Result<Option<Oid>, GitflowError> {
+    Ok(local_branches(repo)?
+        .into_iter()
+        .find(|(name, _)| classify_branch(name, config) == class)
+        .map(|(_, tip)| tip))
+}
+
+/// Returns the tip of the local develop branch, if there is one
+pub(crate) fn develop_tip(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Option<Oid>, GitflowError> {
+    branch_tip(repo, config, BranchClass::Develop)
+}
+
+/// Returns the tip of the local production branch (main or master), if there is one
+pub(crate) fn production_tip(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Option<Oid>, GitflowError> {
+    branch_tip(repo, config, BranchClass::Production)
+}
+
+/// Determines whether HEAD is building the latest develop or an older develop commit, so CI can
+/// warn about rebuilding a stale develop. Returns `None` if there is no develop branch or HEAD
+/// isn't in its history, such as on a feature branch with unmerged commits.
+pub fn develop_position(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Option<DevelopPosition>, GitflowError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let tip = match develop_tip(repo, config)? {
+        Some(tip) => tip,
+        None => return Ok(None),
+    };
+    if head == tip {
+        return Ok(Some(DevelopPosition::Tip));
+    }
+    if !repo.graph_descendant_of(tip, head)? {
+        return Ok(None);
+    }
+    let (behind, _) = repo.graph_ahead_behind(tip, head)?;
+    Ok(Some(DevelopPosition::Behind(behind)))
+}
+
+/// Counts the commits on the current branch that haven't been pushed to its upstream, so local
+/// builds can warn when they're based on unshared commits. Returns `None` if HEAD isn't on a
+/// branch or the branch has no upstream configured. The upstream is compared as of the last
+/// fetch, nothing is fetched.
+///
+/// Also returns `None` if the branch and its upstream have diverged, as after a force push, since
+/// the number of commits ahead then counts rewritten commits that were in fact pushed. See
+/// [`GitflowInfo::history_rewritten`](crate::GitflowInfo::history_rewritten).
+pub fn unpushed_commits(repo: &Repository) -> Result<Option<usize>, GitflowError> {
+    Ok(match upstream_ahead_behind(repo)? {
+        Some((ahead, 0)) => Some(ahead),
+        _ => None,
+    })
+}
+
+/// Returns true if the current branch and its upstream have both gained commits the other
+/// doesn't have, so neither can be fast forwarded to the other. This is what rewriting history
+/// that was already pushed, such as by rebasing or amending, looks like until the rewrite is
+/// force pushed or the upstream is fetched again.
+pub(crate) fn history_rewritten(repo: &Repository) -> Result<bool, GitflowError> {
+    Ok(matches!(upstream_ahead_behind(repo)?, Some((ahead, behind)) if ahead > 0 && behind > 0))
+}
+
+/// Counts the commits the current branch is ahead of and behind its upstream, or `None` if HEAD
+/// isn't on a branch with an upstream
+fn upstream_ahead_behind(repo: &Repository) -> Result<Option<(usize, usize)>, GitflowError> {
+    let head = repo.head()?;
+    if !head.is_branch() {
+        return Ok(None);
+    }
+    let local = head.peel_to_commit()?.id();
+    let upstream = match git2::Branch::wrap(head).upstream() {
+        Ok(upstream) => upstream.get().peel_to_commit()?.id(),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(Some(repo.graph_ahead_behind(local, upstream)?))
+}
+
+/// Lists the full names of the refs pointing at HEAD, such as `refs/heads/develop` and
+/// `refs/tags/v1.2.3`, sorted by name. Annotated tags count as pointing at the commit they tag.
+/// Symbolic refs such as `refs/remotes/origin/HEAD` are left out, as they only alias another ref.
+/// Shows which branches and tags HEAD could be classified by when diagnosing an unexpected
+/// version.
+pub fn refs_at_head(repo: &Repository) -> Result<Vec<String>, GitflowError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let mut names = Vec::new();
+    for reference in repo.references()? {
+        let reference = reference?;
+        if reference.kind() != Some(git2::ReferenceType::Direct) {
+            continue;
+        }
+        let at_head = match reference.peel_to_commit() {
+            Ok(commit) => commit.id() == head,
+            Err(_) => false,
+        };
+        if let (true, Some(name)) = (at_head, reference.name()) {
+            names.push(name.to_owned());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Lists the full names of the refs resolving the version of HEAD depends on: the current
+/// branch, the branch it was cut from (see [`base_branch`]), the version tag nearest to HEAD in
+/// its history, and the highest production tag, which development versions count from.
+/// Duplicates are left out.
+///
+/// A shallow CI checkout can fetch just these instead of cloning everything, for example
+/// `git fetch --depth=<n> origin develop:develop tag v1.2.0` rather than all branches and tags.
+/// The depth must still reach back to the tagged commits, and build numbers, which count
+/// commits, are only correct with the full history between them and HEAD.
+pub fn required_refs(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Vec<String>, GitflowError> {
+    let head_ref = repo.head()?;
+    let head = head_ref.peel_to_commit()?.id();
+    let mut refs = Vec::new();
+    if head_ref.is_branch() {
+        refs.extend(head_ref.name().map(str::to_owned));
+    }
+    if let Some(base) = base_branch(repo, head, config)? {
+        refs.push(format!("refs/heads/{}", base));
+    }
+    let tags = TagIndex::new(repo, config)?;
+    let mut nearest = None;
+    for tag in tags.reachable_from(repo, head)? {
+        let (distance, _) = repo.graph_ahead_behind(head, tag.commit)?;
+        if nearest.as_ref().is_none_or(|&(best, _)| distance < best) {
+            nearest = Some((distance, &tag.name));
+        }
+    }
+    refs.extend(nearest.map(|(_, name)| format!("refs/tags/{}", name)));
+    let latest = tags.latest_production();
+    let latest = tags.tags().iter().find(|tag| match tag.version {
+        VersionInfo::Production(base) => Some(base) == latest,
+        _ => false,
+    });
+    refs.extend(latest.map(|tag| format!("refs/tags/{}", tag.name)));
+    let mut seen = HashSet::new();
+    refs.retain(|name| seen.insert(name.clone()));
+    Ok(refs)
+}
+
+/// Determines which base branch `commit` was cut from, giving the name of the local develop,
+/// production, release, hotfix, or train branch whose merge base with `commit` is closest to it.
+/// Returns `None` if `commit` shares no history with any base branch.
+///
+/// Candidates are ranked by, in order:
+/// 1. The fewest commits in the history of `commit` that aren't on the candidate
+/// 2. Release, hotfix, and train branches over develop, and develop over production, as a branch
+///    cut from a release branch that was just created from develop is also at the tip of develop
+/// 3. The fewest commits on the candidate since the merge base
+/// 4. The branch name
+pub fn base_branch(
+    repo: &Repository,
+    commit: Oid,
+    config: &GitflowConfig,
+) -> Result<Option<String>, GitflowError> {
+    let mut best: Option<(usize, u8, usize, String)> = None;
+    for (name, tip) in local_branches(repo)? {
+        let priority = match classify_branch(&name, config) {
+            BranchClass::Release(_) | BranchClass::Hotfix(_) | BranchClass::Train(_) => 0,
+            BranchClass::Develop => 1,
+            BranchClass::Production => 2,
+            BranchClass::Feature => continue,
+        };
+        match repo.merge_base(commit, tip) {
+            Ok(_) => {}
+            Err(err) if err.code() == git2::ErrorCode::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        }
+        let (ahead, behind) = repo.graph_ahead_behind(commit, tip)?;
+        let candidate = (ahead, priority, behind, name);
+        if best.as_ref().is_none_or(|best| candidate < *best) {
+            best = Some(candidate);
+        }
+    }
+    Ok(best.map(|(_, _, _, name)| name))
+}
+
+/// Compares the channels of the commits `from` and `to`, returning both if they differ, such as
+/// `(Development, Alpha)` when a range of commits moves onto a newly created release branch. This
+/// flags promotions that deployment gates should look at.
+///
+/// Each commit is resolved with [`get_info_for_commit`] on the local branch it is most recent on,
+/// the branch containing it with the fewest commits after it, taking the first by name on ties.
+/// Fails with [`GitflowError::NoBranch`] if no branch contains a commit.
+pub fn channel_transition(
+    repo: &Repository,
+    from: Oid,
+    to: Oid,
+    config: &GitflowConfig,
+) -> Result<Option<(Channel, Channel)>, GitflowError> {
+    let from = commit_channel(repo, from, config)?;
+    let to = commit_channel(repo, to, config)?;
+    Ok(if from == to { None } else { Some((from, to)) })
+}
+
+/// Resolves the channel of `commit` on the branch [`channel_transition`] picks for it
+fn commit_channel(
+    repo: &Repository,
+    commit: Oid,
+    config: &GitflowConfig,
+) -> Result<Channel, GitflowError> {
+    let mut best: Option<(usize, String)> = None;
+    for (name, tip) in local_branches(repo)? {
+        if tip != commit && !repo.graph_descendant_of(tip, commit)? {
+            continue;
+        }
+        let (_, after) = repo.graph_ahead_behind(commit, tip)?;
+        if best
+            .as_ref()
+            .is_none_or(|best| (after, &name) < (best.0, &best.1))
+        {
+            best = Some((after, name));
+        }
+    }
+    let branch = match best {
+        Some((_, name)) => name,
+        None => {
+            return Err(GitflowError::NoBranch {
+                commit: format_hash(commit, config),
+            })
+        }
+    };
+    Ok(get_info_for_commit(repo, commit, &branch, config)?
+        .version
+        .channel())
+}
+
+/// Returns the commit the release branch HEAD is on diverged from develop, their merge base. This
+/// is the anchor of the branch's release candidates: the commits after it, up to the first rc
+/// tag, are `rc.1`.
+///
+/// Fails with [`GitflowError::NotReleaseBranch`] if HEAD isn't on a release or train branch, and
+/// with [`GitflowError::NoBranchPoint`] if there is no develop branch or it shares no history
+/// with the release branch.
+pub fn release_branch_point(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Oid, GitflowError> {
+    let head = repo.head()?;
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+    if !head.is_branch()
+        || !matches!(
+            classify_branch(&branch, config),
+            BranchClass::Release(_) | BranchClass::Train(_)
+        )
+    {
+        return Err(GitflowError::NotReleaseBranch { branch });
+    }
+    let head = head.peel_to_commit()?.id();
+    let develop = match develop_tip(repo, config)? {
+        Some(develop) => develop,
+        None => return Err(GitflowError::NoBranchPoint { branch }),
+    };
+    match repo.merge_base(head, develop) {
+        Ok(base) => Ok(base),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            Err(GitflowError::NoBranchPoint { branch })
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns true if `commit` shares no history with the develop or production branch, as happens
+/// for the first commits of a branch created with `git checkout --orphan`. Repositories without
+/// either branch have no orphans.
+pub fn is_orphan(
+    repo: &Repository,
+    commit: Oid,
+    config: &GitflowConfig,
+) -> Result<bool, GitflowError> {
+    let tips = [develop_tip(repo, config)?, production_tip(repo, config)?];
+    let mut orphan = false;
+    for tip in tips.into_iter().flatten() {
+        match repo.merge_base(commit, tip) {
+            Ok(_) => return Ok(false),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => orphan = true,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(orphan)
+}
+
+/// Returns true if HEAD is in the history of a local release or hotfix branch but not of develop
+/// or the production branch, meaning it is release work that hasn't been merged back yet. Gives
+/// false for commits on no release branch, such as develop, feature, and production commits, and
+/// for release branch commits that were already merged.
+pub fn is_release_only(repo: &Repository, config: &GitflowConfig) -> Result<bool, GitflowError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let contains = |tip: Oid| -> Result<bool, GitflowError> {
+        Ok(tip == head || repo.graph_descendant_of(tip, head)?)
+    };
+    let mut on_release = false;
+    for (name, tip) in local_branches(repo)? {
+        match classify_branch(&name, config) {
+            BranchClass::Release(_) | BranchClass::Hotfix(_) => on_release |= contains(tip)?,
+            BranchClass::Develop | BranchClass::Production if contains(tip)? => return Ok(false),
+            _ => {}
+        }
+    }
+    Ok(on_release)
+}
+
+/// Summary of how HEAD differs from what is in production
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub struct ProductionDelta {
+    /// Channel of HEAD
+    pub channel: Channel,
+
+    /// Latest production release in the history of HEAD, which HEAD builds upon
+    pub base: Option<SemverBase>,
+
+    /// Number of commits in HEAD that aren't on the production branch. Without a production
+    /// branch, the commits since the latest production release.
+    pub commits_ahead: usize,
+
+    /// Whether HEAD contains changes that haven't been released, so merging it will require a new
+    /// release
+    pub bump_pending: bool,
+}
+
+/// Summarizes what HEAD would do to the version, giving pull request reviewers an at a glance view
+/// of its channel, the release it builds on, and how far it is ahead of production.
+pub fn production_delta(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<ProductionDelta, GitflowError> {
+    let info = get_info_for_repo(repo, config)?;
+    let head = repo.head()?.peel_to_commit()?.id();
+
+    let tags = TagIndex::new(repo, config)?;
+    let latest = tags
+        .reachable_from(repo, head)?
+        .into_iter()
+        .filter(|tag| matches!(tag.version, VersionInfo::Production(_)))
+        .max_by_key(|tag| tag.version);
+    let base = latest.map(|tag| match tag.version {
+        VersionInfo::Production(base) => base,
+        _ => unreachable!(),
+    });
+
+    let commits_ahead = match (production_tip(repo, config)?, latest) {
+        (Some(tip), _) => repo.graph_ahead_behind(head, tip)?.0,
+        (None, Some(latest)) => repo.graph_ahead_behind(head, latest.commit)?.0,
+        (None, None) => {
+            let mut revwalk = repo.revwalk()?;
+            revwalk.push(head)?;
+            revwalk.count()
+        }
+    };
+
+    Ok(ProductionDelta {
+        channel: info.version.channel(),
+        base,
+        commits_ahead,
+        bump_pending: commits_ahead > 0,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+
+    #[test]
+    fn release_only_commits() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        assert!(!is_release_only(&repo.repo, &GitflowConfig::default()).unwrap());
+
+        repo.branch("release/v1.1.0");
+        assert!(!is_release_only(&repo.repo, &GitflowConfig::default()).unwrap());
+        repo.commit("release fix");
+        assert!(is_release_only(&repo.repo, &GitflowConfig::default()).unwrap());
+
+        repo.checkout("develop");
+        repo.merge("release/v1.1.0", "Merge release/v1.1.0");
+        repo.checkout("release/v1.1.0");
+        assert!(!is_release_only(&repo.repo, &GitflowConfig::default()).unwrap());
+        repo.checkout("main");
+        assert!(!is_release_only(&repo.repo, &GitflowConfig::default()).unwrap());
+    }
+
+    #[test]
+    fn refs_required_for_resolution() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("develop");
+        repo.branch("feature/login");
+        repo.commit("login");
+        repo.checkout("main");
+        repo.commit("hotfix");
+        repo.tag("v1.0.1");
+        repo.checkout("feature/login");
+
+        assert_eq!(
+            required_refs(&repo.repo, &GitflowConfig::default()).unwrap(),
+            vec![
+                "refs/heads/feature/login",
+                "refs/heads/develop",
+                "refs/tags/v1.0.0",
+                "refs/tags/v1.0.1",
+            ]
+        );
+        repo.checkout("main");
+        assert_eq!(
+            required_refs(&repo.repo, &GitflowConfig::default()).unwrap(),
+            vec!["refs/heads/main", "refs/tags/v1.0.1"]
+        );
+    }
+
+    #[test]
+    fn position_on_develop() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        assert_eq!(
+            develop_position(&repo.repo, &GitflowConfig::default()).unwrap(),
+            None
+        );
+
+        repo.branch("develop");
+        let old = repo.commit("first feature");
+        repo.commit("second feature");
+        repo.commit("third feature");
+        assert_eq!(
+            develop_position(&repo.repo, &GitflowConfig::default()).unwrap(),
+            Some(DevelopPosition::Tip)
+        );
+
+        repo.repo.set_head_detached(old).unwrap();
+        assert_eq!(
+            develop_position(&repo.repo, &GitflowConfig::default()).unwrap(),
+            Some(DevelopPosition::Behind(2))
+        );
+
+        repo.checkout("develop");
+        repo.branch("login-page");
+        repo.commit("login page");
+        assert_eq!(
+            develop_position(&repo.repo, &GitflowConfig::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn delta_ahead_of_production() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        assert_eq!(
+            production_delta(&repo.repo, &GitflowConfig::default()).unwrap(),
+            ProductionDelta {
+                channel: Channel::Production,
+                base: Some(SemverBase {
+                    major: 1,
+                    minor: 0,
+                    patch: 0
+                }),
+                commits_ahead: 0,
+                bump_pending: false,
+            }
+        );
+
+        repo.branch("develop");
+        repo.branch("feature/login");
+        repo.commit("login page");
+        repo.commit("login tests");
+        assert_eq!(
+            production_delta(&repo.repo, &GitflowConfig::default()).unwrap(),
+            ProductionDelta {
+                channel: Channel::Local,
+                base: Some(SemverBase {
+                    major: 1,
+                    minor: 0,
+                    patch: 0
+                }),
+                commits_ahead: 2,
+                bump_pending: true,
+            }
+        );
+    }
+
+    #[test]
+    fn orphan_branches() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        let develop = repo.commit("feature");
+        let config = GitflowConfig::default();
+        assert!(!is_orphan(&repo.repo, develop, &config).unwrap());
+
+        repo.orphan_branch("release/v2.0.0");
+        repo.commit("docs");
+        let orphan = repo.commit("more docs");
+        assert!(is_orphan(&repo.repo, orphan, &config).unwrap());
+
+        let info = get_info_for_repo(&repo.repo, &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Local);
+        assert_eq!(info.build_number, 2);
+        assert_eq!(
+            production_delta(&repo.repo, &GitflowConfig::default())
+                .unwrap()
+                .commits_ahead,
+            2
+        );
+    }
+
+    #[test]
+    fn refs_pointing_at_head() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.1.0");
+        repo.tag("v1.1.0-rc.1");
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.repo
+            .tag("v1.1.0-rc.1-annotated", head.as_object(), &sig, "rc", false)
+            .unwrap();
+        repo.repo
+            .reference_symbolic("refs/remotes/origin/HEAD", "refs/heads/develop", false, "")
+            .unwrap();
+
+        assert_eq!(
+            refs_at_head(&repo.repo).unwrap(),
+            [
+                "refs/heads/develop",
+                "refs/heads/release/v1.1.0",
+                "refs/tags/v1.1.0-rc.1",
+                "refs/tags/v1.1.0-rc.1-annotated",
+            ]
+        );
+    }
+
+    #[test]
+    fn unpushed_commits_against_upstream() {
+        let repo = TestRepo::new();
+        let pushed = repo.commit("initial");
+        assert_eq!(unpushed_commits(&repo.repo).unwrap(), None);
+
+        repo.repo
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.repo
+            .reference("refs/remotes/origin/main", pushed, false, "fetch")
+            .unwrap();
+        repo.repo
+            .find_branch("main", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/main"))
+            .unwrap();
+        assert_eq!(unpushed_commits(&repo.repo).unwrap(), Some(0));
+
+        repo.commit("local fix");
+        repo.commit("another local fix");
+        assert_eq!(unpushed_commits(&repo.repo).unwrap(), Some(2));
+        assert!(!history_rewritten(&repo.repo).unwrap());
+    }
+
+    #[test]
+    fn diverged_from_upstream() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let pushed = repo.commit("pushed fix");
+        repo.repo
+            .remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.repo
+            .reference("refs/remotes/origin/main", pushed, false, "fetch")
+            .unwrap();
+        repo.repo
+            .find_branch("main", git2::BranchType::Local)
+            .unwrap()
+            .set_upstream(Some("origin/main"))
+            .unwrap();
+
+        // Amend the pushed commit
+        let parent = repo.repo.find_commit(pushed).unwrap().parent(0).unwrap();
+        repo.repo
+            .reset(parent.as_object(), git2::ResetType::Soft, None)
+            .unwrap();
+        repo.commit("amended fix");
+        assert!(history_rewritten(&repo.repo).unwrap());
+        assert_eq!(unpushed_commits(&repo.repo).unwrap(), None);
+        let info = get_info_for_repo(&repo.repo, &GitflowConfig::default());
+        assert!(info.unwrap().history_rewritten);
+    }
+
+    #[test]
+    fn release_branch_creation_transition() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        let feature = repo.commit("feature");
+        let next = repo.commit("next feature");
+        repo.branch("release/v1.1.0");
+        let fix = repo.commit("fix");
+
+        assert_eq!(
+            channel_transition(&repo.repo, feature, next, &GitflowConfig::default()).unwrap(),
+            None
+        );
+        assert_eq!(
+            channel_transition(&repo.repo, next, fix, &GitflowConfig::default()).unwrap(),
+            Some((Channel::Development, Channel::Alpha))
+        );
+        assert_eq!(
+            channel_transition(&repo.repo, fix, feature, &GitflowConfig::default()).unwrap(),
+            Some((Channel::Alpha, Channel::Development))
+        );
+    }
+
+    #[test]
+    fn feature_off_release_branch() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.2.0");
+        repo.commit("fix");
+        repo.commit("another fix");
+        repo.branch("feature/hotter-fix");
+        let feature = repo.commit("hotter fix");
+        repo.checkout("develop");
+        repo.commit("next feature");
+
+        let config = GitflowConfig::default();
+        assert_eq!(
+            base_branch(&repo.repo, feature, &config)
+                .unwrap()
+                .as_deref(),
+            Some("release/v1.2.0")
+        );
+
+        repo.branch("feature/login");
+        let feature = repo.commit("login page");
+        assert_eq!(
+            base_branch(&repo.repo, feature, &config)
+                .unwrap()
+                .as_deref(),
+            Some("develop")
+        );
+        assert_eq!(
+            get_info_for_repo(&repo.repo, &config)
+                .unwrap()
+                .base_branch
+                .as_deref(),
+            Some("develop")
+        );
+
+        // Freshly cut release branches win over the develop commit they were cut from
+        repo.checkout("develop");
+        repo.branch("release/v1.3.0");
+        repo.branch("feature/polish");
+        let feature = repo.commit("polish");
+        assert_eq!(
+            base_branch(&repo.repo, feature, &config)
+                .unwrap()
+                .as_deref(),
+            Some("release/v1.3.0")
+        );
+    }
+
+    #[test]
+    fn release_branch_points() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        let cut = repo.commit("feature");
+        assert!(matches!(
+            release_branch_point(&repo.repo, &GitflowConfig::default()),
+            Err(GitflowError::NotReleaseBranch { .. })
+        ));
+
+        repo.branch("release/v1.2.0");
+        assert_eq!(
+            release_branch_point(&repo.repo, &GitflowConfig::default()).unwrap(),
+            cut
+        );
+        repo.commit("fix");
+        repo.checkout("develop");
+        repo.commit("next feature");
+        repo.checkout("release/v1.2.0");
+        repo.commit("another fix");
+        assert_eq!(
+            release_branch_point(&repo.repo, &GitflowConfig::default()).unwrap(),
+            cut
+        );
+
+        repo.orphan_branch("release/v2.0.0");
+        repo.commit("rewrite");
+        assert!(matches!(
+            release_branch_point(&repo.repo, &GitflowConfig::default()),
+            Err(GitflowError::NoBranchPoint { .. })
+        ));
+    }
+
+    #[test]
+    fn replace_refs_truncate_history() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        let old = repo.commit("feature");
+        repo.commit("another feature");
+
+        // Graft `old` onto nothing, cutting off the initial commit
+        let commit = repo.repo.find_commit(old).unwrap();
+        let grafted = repo
+            .repo
+            .commit(
+                None,
+                &commit.author(),
+                &commit.committer(),
+                "feature",
+                &commit.tree().unwrap(),
+                &[],
+            )
+            .unwrap();
+        repo.repo
+            .reference(&format!("refs/replace/{}", old), grafted, false, "replace")
+            .unwrap();
+
+        let head = repo.repo.head().unwrap().target().unwrap();
+        let config = GitflowConfig::default();
+        let follow = GitflowConfig {
+            follow_replace_refs: true,
+            ..Default::default()
+        };
+        assert_eq!(count_history(&repo.repo, head, &config).unwrap(), 3);
+        assert_eq!(count_history(&repo.repo, head, &follow).unwrap(), 2);
+        assert_eq!(
+            get_info_for_repo(&repo.repo, &follow).unwrap().build_number,
+            2
+        );
+
+        let reachable = |config| {
+            TagIndex::new(&repo.repo, config)
+                .unwrap()
+                .reachable_from(&repo.repo, head)
+                .unwrap()
+                .len()
+        };
+        assert_eq!(reachable(&config), 1);
+        assert_eq!(reachable(&follow), 0);
+    }
+}
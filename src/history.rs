@@ -0,0 +1,859 @@
+//! Queries over the release history of a repository
+
+use crate::{
+    classify_branch, format_hash, next_development_base, parse_semver, BranchClass, GitflowConfig,
+    GitflowError, SemverBase, SemverRC, TagIndex, VersionInfo, VersionTag,
+};
+use git2::{Oid, Repository};
+use std::collections::HashMap;
+
+/// File at the root of the tree holding the version of the project, e.g. `1.2.3` or `v1.2.3`
+pub const VERSION_FILE: &str = "VERSION";
+
+/// Lists each production release with the number of seconds since the release before it, in
+/// chronological order of the tagged commits' commit times. The first release has no previous
+/// release to measure from and is omitted.
+pub fn release_intervals(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Vec<(SemverBase, i64)>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut releases = Vec::new();
+    for tag in tags.tags() {
+        if let VersionInfo::Production(base) = tag.version {
+            let time = repo.find_commit(tag.commit)?.time().seconds();
+            releases.push((time, base));
+        }
+    }
+    releases.sort();
+    Ok(releases
+        .windows(2)
+        .map(|pair| (pair[1].1, pair[1].0 - pair[0].0))
+        .collect())
+}
+
+/// The commits that went into a production release since the release before it
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct ReleaseWindow {
+    pub version: SemverBase,
+
+    /// Commit of the previous release, or `None` for the first release, whose window starts at
+    /// the beginning of history
+    pub start: Option<Oid>,
+
+    /// Commit the release was tagged on
+    pub end: Oid,
+
+    /// Number of commits in the history of `end` that aren't in the history of `start`
+    pub commit_count: usize,
+
+    /// Number of distinct author emails among those commits
+    pub author_count: usize,
+}
+
+/// Splits the history of the production releases into [`ReleaseWindow`]s, one per release in
+/// version order. Each window is walked once with the previous release hidden, so commits are
+/// only visited by the window they were released in.
+pub fn release_windows(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Vec<ReleaseWindow>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut releases: Vec<(SemverBase, Oid)> = tags
+        .tags()
+        .iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some((base, tag.commit)),
+            _ => None,
+        })
+        .collect();
+    releases.sort();
+    releases.dedup_by_key(|(base, _)| *base);
+
+    let mut windows = Vec::with_capacity(releases.len());
+    let mut start = None;
+    for (version, end) in releases {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(end)?;
+        if let Some(start) = start {
+            revwalk.hide(start)?;
+        }
+        let mut commit_count = 0;
+        let mut authors = std::collections::HashSet::new();
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            commit_count += 1;
+            authors.insert(commit.author().email_bytes().to_vec());
+        }
+        windows.push(ReleaseWindow {
+            version,
+            start,
+            end,
+            commit_count,
+            author_count: authors.len(),
+        });
+        start = Some(end);
+    }
+    Ok(windows)
+}
+
+/// Lists the production versions that shipped the commit `oid`, those whose tagged commit is
+/// `oid` or has it as an ancestor, in ascending order. Answers "which releases include my fix".
+pub fn releases_containing(
+    repo: &Repository,
+    oid: Oid,
+    config: &GitflowConfig,
+) -> Result<Vec<SemverBase>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut releases = Vec::new();
+    for tag in tags.tags() {
+        if let VersionInfo::Production(base) = tag.version {
+            if tag.commit == oid || repo.graph_descendant_of(tag.commit, oid)? {
+                releases.push(base);
+            }
+        }
+    }
+    releases.sort();
+    releases.dedup();
+    Ok(releases)
+}
+
+/// Checks whether the commit `oid` changed the version of the project, returning the versions
+/// before and after it if so.
+///
+/// A commit bumps the version if it changes the contents of the [`VERSION_FILE`] from one version
+/// to another, compared to its first parent. Otherwise, a commit with a production tag bumps the
+/// version if the newest production release in the history of its first parent is a different
+/// version. Root commits have no previous version and are never bumps.
+pub fn is_version_bump_commit(
+    repo: &Repository,
+    oid: Oid,
+    config: &GitflowConfig,
+) -> Result<Option<(SemverBase, SemverBase)>, GitflowError> {
+    let commit = repo.find_commit(oid)?;
+    let parent = match commit.parents().next() {
+        Some(parent) => parent,
+        None => return Ok(None),
+    };
+
+    let old = version_file(repo, &parent)?;
+    let new = version_file(repo, &commit)?;
+    if let (Some(old), Some(new)) = (old, new) {
+        return Ok(Some((old, new)).filter(|_| old != new));
+    }
+
+    let tags = TagIndex::new(repo, config)?;
+    let new = tags.at(oid).find_map(|tag| match tag.version {
+        VersionInfo::Production(base) => Some(base),
+        _ => None,
+    });
+    let new = match new {
+        Some(new) => new,
+        None => return Ok(None),
+    };
+    let old = tags
+        .reachable_from(repo, parent.id())?
+        .into_iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some(base),
+            _ => None,
+        })
+        .max();
+    Ok(old.filter(|old| *old != new).map(|old| (old, new)))
+}
+
+/// Returns the bounds of the unreleased changes on HEAD for changelog generators such as
+/// `git-cliff`: the name of the newest production tag in the history of HEAD, and the hash of HEAD,
+/// abbreviated to [`GitflowConfig::hash_length`] if that is set. The range is `from..to` in git
+/// revision syntax. `from` is `None` if nothing has been released yet, meaning the range starts at
+/// the beginning of history.
+pub fn changelog_range(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<(Option<String>, String), GitflowError> {
+    let (from, head) = unreleased_range(repo, config)?;
+    Ok((from.map(|tag| tag.name), format_hash(head, config)))
+}
+
+/// Finds the newest production tag in the history of HEAD and HEAD itself, the bounds of
+/// [`changelog_range`]
+fn unreleased_range(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<(Option<VersionTag>, Oid), GitflowError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let tags = TagIndex::new(repo, config)?;
+    let from = tags
+        .reachable_from(repo, head)?
+        .into_iter()
+        .filter(|tag| matches!(tag.version, VersionInfo::Production(_)))
+        .max_by_key(|tag| tag.version)
+        .cloned();
+    Ok((from, head))
+}
+
+/// Semver increment a set of changes calls for, ordered from smallest to largest
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, Hash)]
+pub enum BumpKind {
+    /// No commits call for a release
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+/// Suggests the next version increment from the [conventional commit] messages of the unreleased
+/// changes on HEAD, the [`changelog_range`]. The largest bump of any commit wins. Each message is
+/// classified as:
+///
+/// - [`BumpKind::Major`] if its header has a `!` before the colon, as in `feat!:` or
+///   `fix(parser)!:`, or any line starts with `BREAKING CHANGE:` or `BREAKING-CHANGE:`
+/// - [`BumpKind::Minor`] if its type is `feat`
+/// - [`BumpKind::Patch`] if its type is `fix`
+/// - [`BumpKind::None`] otherwise, including other types such as `docs` or `chore` and messages
+///   that aren't conventional commits
+///
+/// Types are matched case insensitively, and the scope in parentheses is ignored.
+///
+/// [conventional commit]: https://www.conventionalcommits.org/en/v1.0.0/
+pub fn suggested_bump(repo: &Repository, config: &GitflowConfig) -> Result<BumpKind, GitflowError> {
+    let (from, head) = unreleased_range(repo, config)?;
+    let mut walk = repo.revwalk()?;
+    walk.push(head)?;
+    if let Some(from) = from {
+        walk.hide(from.commit)?;
+    }
+    let mut bump = BumpKind::None;
+    for oid in walk {
+        let commit = repo.find_commit(oid?)?;
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        bump = bump.max(commit_bump(&message));
+    }
+    Ok(bump)
+}
+
+/// Classifies a single commit message following the rules of [`suggested_bump`]
+fn commit_bump(message: &str) -> BumpKind {
+    let breaking_footer = message
+        .lines()
+        .any(|line| line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:"));
+    let header = message.lines().next().unwrap_or("");
+    let kind = match header.split_once(':') {
+        Some((kind, _)) => kind.trim(),
+        None => {
+            return if breaking_footer {
+                BumpKind::Major
+            } else {
+                BumpKind::None
+            }
+        }
+    };
+    let (kind, breaking) = match kind.strip_suffix('!') {
+        Some(kind) => (kind, true),
+        None => (kind, false),
+    };
+    let kind = match kind.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        _ => kind,
+    };
+    if !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return if breaking_footer {
+            BumpKind::Major
+        } else {
+            BumpKind::None
+        };
+    }
+    if breaking || breaking_footer {
+        BumpKind::Major
+    } else if kind.eq_ignore_ascii_case("feat") {
+        BumpKind::Minor
+    } else if kind.eq_ignore_ascii_case("fix") {
+        BumpKind::Patch
+    } else {
+        BumpKind::None
+    }
+}
+
+/// Returns the `n`th most recent production release and the commit it tagged, counting down from
+/// the highest version: `n = 0` is the latest release, `n = 1` the one before it, and so on. Gives
+/// rollback tooling the release to go back to. Returns `None` if there are `n` or fewer releases.
+pub fn nth_latest_release(
+    repo: &Repository,
+    n: usize,
+    config: &GitflowConfig,
+) -> Result<Option<(SemverBase, Oid)>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut releases: Vec<_> = tags
+        .tags()
+        .iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some((base, tag.commit)),
+            _ => None,
+        })
+        .collect();
+    releases.sort_by_key(|release| std::cmp::Reverse(release.0));
+    releases.dedup_by_key(|release| release.0);
+    Ok(releases.get(n).copied())
+}
+
+/// Lists the production releases newer than `of`, oldest first, such as those published since the
+/// version a running binary was built as. The length is how many releases behind it is.
+/// Release candidates don't count.
+pub fn releases_ahead(
+    repo: &Repository,
+    of: &SemverBase,
+    config: &GitflowConfig,
+) -> Result<Vec<SemverBase>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut releases: Vec<_> = tags.production().filter(|base| base > of).collect();
+    releases.sort();
+    releases.dedup();
+    Ok(releases)
+}
+
+/// The tags that would be created next in each channel, see [`next_tags`]
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub struct NextTags {
+    /// Release the release or hotfix branch HEAD is on would ship as, once it has a release
+    /// candidate and hasn't been released yet
+    pub production: Option<SemverBase>,
+
+    /// Release candidate after the latest one in the history of HEAD, when HEAD is on a release or
+    /// hotfix branch that hasn't been released yet
+    pub rc: Option<SemverRC>,
+
+    /// Base version develop works towards, the minor version after the highest release, release
+    /// candidate, or release branch
+    pub development_base: Option<SemverBase>,
+}
+
+/// Reports the next tag of each channel for a release dashboard. Which fields are set depends on
+/// the state of the repository and the branch HEAD is on, see [`NextTags`].
+pub fn next_tags(repo: &Repository, config: &GitflowConfig) -> Result<NextTags, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut next = NextTags {
+        production: None,
+        rc: None,
+        development_base: next_development_base(repo, &tags, config)?,
+    };
+    let head = repo.head()?;
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+    let base = match classify_branch(&branch, config) {
+        BranchClass::Release(base) | BranchClass::Hotfix(base) => base,
+        _ => return Ok(next),
+    };
+    if tags.production().any(|released| released == base) {
+        return Ok(next);
+    }
+    let latest_rc = tags.latest_rc(repo, head.peel_to_commit()?.id(), base)?;
+    next.production = latest_rc.map(|_| base);
+    next.rc = Some(SemverRC {
+        base,
+        rc: latest_rc.map_or(1, |rc| rc.saturating_add(1)),
+    });
+    Ok(next)
+}
+
+/// Maps each package in [`GitflowConfig::packages`] to its latest production release in the history
+/// of HEAD, the release of its prefixed tags with the highest version, for writing release
+/// manifests such as release-please's `.release-please-manifest.json`. Packages with no release
+/// yet are left out. The tags are read into a single [`TagIndex`] like the unprefixed ones, each
+/// matched to the package with the longest prefix of its name.
+pub fn manifest_versions(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<HashMap<String, VersionInfo>, GitflowError> {
+    let package = |name: &str| {
+        config
+            .packages
+            .iter()
+            .filter(|(_, prefix)| name.starts_with(prefix.as_str()))
+            .max_by_key(|(_, prefix)| prefix.len())
+    };
+    let tags = TagIndex::with_parser(repo, config, |name| {
+        let (_, prefix) = package(name)?;
+        match config.parse_version(&name[prefix.len()..])? {
+            version @ VersionInfo::Production(_) => Some(version),
+            _ => None,
+        }
+    })?;
+    let head = repo.head()?.peel_to_commit()?.id();
+    let mut versions = HashMap::new();
+    for tag in tags.reachable_from(repo, head)? {
+        if let (Some((path, _)), VersionInfo::Production(base)) = (package(&tag.name), tag.version)
+        {
+            let latest = versions.entry(path.clone()).or_insert(base);
+            *latest = base.max(*latest);
+        }
+    }
+    Ok(versions
+        .into_iter()
+        .map(|(path, base)| (path, VersionInfo::Production(base)))
+        .collect())
+}
+
+/// Reads the version in the [`VERSION_FILE`] of `commit`, if it has one that parses
+fn version_file(
+    repo: &Repository,
+    commit: &git2::Commit,
+) -> Result<Option<SemverBase>, GitflowError> {
+    let tree = commit.tree()?;
+    let entry = match tree.get_name(VERSION_FILE) {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let blob = match entry.to_object(repo)?.into_blob() {
+        Ok(blob) => blob,
+        Err(_) => return Ok(None),
+    };
+    let contents = String::from_utf8_lossy(blob.content());
+    let contents = contents.trim();
+    let version = if contents.starts_with('v') {
+        parse_semver(contents)
+    } else {
+        parse_semver(&format!("v{}", contents))
+    };
+    Ok(match version {
+        Ok(VersionInfo::Production(base)) => Some(base),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util::TestRepo, TagFilter};
+
+    fn base(minor: u8) -> SemverBase {
+        SemverBase {
+            major: 1,
+            minor,
+            patch: 0,
+        }
+    }
+
+    #[test]
+    fn intervals_between_releases() {
+        let repo = TestRepo::new();
+        repo.commit_at("initial", 1_000_000);
+        repo.tag("v1.0.0");
+        repo.commit_at("second", 1_086_400);
+        repo.tag("v1.1.0");
+        repo.commit_at("not released", 1_090_000);
+        repo.commit_at("third", 1_100_000);
+        repo.tag("v1.2.0");
+        repo.tag("v1.2.0-rc.1");
+
+        assert_eq!(
+            release_intervals(&repo.repo, &GitflowConfig::default()).unwrap(),
+            [(base(1), 86_400), (base(2), 13_600)]
+        );
+    }
+
+    #[test]
+    fn single_release_has_no_interval() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        assert_eq!(
+            release_intervals(&repo.repo, &GitflowConfig::default()).unwrap(),
+            []
+        );
+    }
+
+    #[test]
+    fn version_file_bump() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        let first = repo.commit_file(VERSION_FILE, "1.0.0\n", "add version file");
+        let unrelated = repo.commit("unrelated change");
+        let bump = repo.commit_file(VERSION_FILE, "v1.1.0\n", "bump version");
+
+        assert_eq!(
+            is_version_bump_commit(&repo.repo, first, &GitflowConfig::default()).unwrap(),
+            None
+        );
+        assert_eq!(
+            is_version_bump_commit(&repo.repo, unrelated, &GitflowConfig::default()).unwrap(),
+            None
+        );
+        assert_eq!(
+            is_version_bump_commit(&repo.repo, bump, &GitflowConfig::default()).unwrap(),
+            Some((base(0), base(1)))
+        );
+    }
+
+    #[test]
+    fn tagged_bump() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let unreleased = repo.commit("feature");
+        let release = repo.commit("release");
+        repo.tag("v1.1.0");
+
+        assert_eq!(
+            is_version_bump_commit(&repo.repo, unreleased, &GitflowConfig::default()).unwrap(),
+            None
+        );
+        assert_eq!(
+            is_version_bump_commit(&repo.repo, release, &GitflowConfig::default()).unwrap(),
+            Some((base(0), base(1)))
+        );
+    }
+
+    #[test]
+    fn changelog_range_since_release() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("fix");
+        repo.tag("v1.0.1");
+        repo.commit("feature");
+        repo.tag("v1.1.0-rc.1");
+        let head = repo.commit("another feature");
+        assert_eq!(
+            changelog_range(&repo.repo, &GitflowConfig::default()).unwrap(),
+            (Some("v1.0.1".to_owned()), head.to_string())
+        );
+    }
+
+    #[test]
+    fn changelog_range_first_release() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        let head = repo.commit("feature");
+        assert_eq!(
+            changelog_range(&repo.repo, &GitflowConfig::default()).unwrap(),
+            (None, head.to_string())
+        );
+    }
+
+    #[test]
+    fn windows_between_releases() {
+        let repo = TestRepo::new();
+        let first = repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("feature");
+        repo.commit_as("another feature", "Other", "other@example.com");
+        let second = repo.commit("release");
+        repo.tag("v1.1.0");
+        let third = repo.commit("fix");
+        repo.tag("v1.1.1");
+        repo.commit("unreleased");
+
+        assert_eq!(
+            release_windows(&repo.repo, &GitflowConfig::default()).unwrap(),
+            [
+                ReleaseWindow {
+                    version: base(0),
+                    start: None,
+                    end: first,
+                    commit_count: 1,
+                    author_count: 1,
+                },
+                ReleaseWindow {
+                    version: base(1),
+                    start: Some(first),
+                    end: second,
+                    commit_count: 3,
+                    author_count: 2,
+                },
+                ReleaseWindow {
+                    version: SemverBase {
+                        major: 1,
+                        minor: 1,
+                        patch: 1,
+                    },
+                    start: Some(second),
+                    end: third,
+                    commit_count: 1,
+                    author_count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn releases_containing_fix() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let fix = repo.commit("fix");
+        repo.tag("v1.1.0-rc.1");
+        repo.commit("feature");
+        repo.tag("v1.1.0");
+        repo.branch("hotfix/v1.1.1");
+        repo.commit("hotfix");
+        repo.tag("v1.1.1");
+        repo.checkout("main");
+        repo.commit("next feature");
+        repo.tag("v1.2.0");
+        let unreleased = repo.commit("unreleased");
+
+        assert_eq!(
+            releases_containing(&repo.repo, fix, &GitflowConfig::default()).unwrap(),
+            [
+                base(1),
+                SemverBase {
+                    major: 1,
+                    minor: 1,
+                    patch: 1,
+                },
+                base(2),
+            ]
+        );
+        assert_eq!(
+            releases_containing(&repo.repo, unreleased, &GitflowConfig::default()).unwrap(),
+            []
+        );
+    }
+
+    #[test]
+    fn commit_bump_signals() {
+        assert_eq!(commit_bump("feat: add parser"), BumpKind::Minor);
+        assert_eq!(commit_bump("Feat(cli): add flag"), BumpKind::Minor);
+        assert_eq!(commit_bump("fix: off by one"), BumpKind::Patch);
+        assert_eq!(commit_bump("fix(tags)!: drop v prefix"), BumpKind::Major);
+        assert_eq!(commit_bump("feat!: new api"), BumpKind::Major);
+        assert_eq!(
+            commit_bump("refactor: rename\n\nBREAKING CHANGE: renamed get_info"),
+            BumpKind::Major
+        );
+        assert_eq!(commit_bump("docs: typo"), BumpKind::None);
+        assert_eq!(commit_bump("Update README"), BumpKind::None);
+        assert_eq!(commit_bump("Merge branch 'feat: x'"), BumpKind::None);
+    }
+
+    #[test]
+    fn suggested_bump_since_release() {
+        let repo = TestRepo::new();
+        repo.commit("feat!: initial api");
+        repo.tag("v1.0.0");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::None
+        );
+
+        repo.commit("docs: readme");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::None
+        );
+        repo.commit("fix: crash");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::Patch
+        );
+        repo.commit("feat: new flag");
+        repo.commit("fix: another crash");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::Minor
+        );
+        repo.commit("chore: cleanup\n\nBREAKING CHANGE: removed flag");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::Major
+        );
+
+        repo.tag("v2.0.0");
+        assert_eq!(
+            suggested_bump(&repo.repo, &GitflowConfig::default()).unwrap(),
+            BumpKind::None
+        );
+
+        // The tagged commit is used, not whatever else shares the tag's name
+        let initial = repo.repo.revparse_single("v1.0.0").unwrap();
+        repo.repo
+            .reference("refs/v2.0.0", initial.id(), false, "test")
+            .unwrap();
+        repo.commit("fix: after the release");
+        let config = GitflowConfig {
+            hash_length: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            suggested_bump(&repo.repo, &config).unwrap(),
+            BumpKind::Patch
+        );
+    }
+
+    #[test]
+    fn manifest_versions_per_package() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("core-v1.0.0");
+        repo.tag("core-cli-v0.1.0");
+        repo.commit("core fix");
+        repo.tag("core-v1.0.1");
+        repo.tag("core-v1.1.0-rc.1");
+        repo.tag("v9.0.0");
+        repo.commit("cli feature");
+        repo.tag("core-cli-v0.2.0");
+
+        let config = GitflowConfig {
+            packages: HashMap::from([
+                ("packages/core".to_owned(), "core-".to_owned()),
+                ("packages/cli".to_owned(), "core-cli-".to_owned()),
+                ("packages/unreleased".to_owned(), "new-".to_owned()),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(
+            manifest_versions(&repo.repo, &config).unwrap(),
+            HashMap::from([
+                (
+                    "packages/core".to_owned(),
+                    VersionInfo::Production(SemverBase {
+                        major: 1,
+                        minor: 0,
+                        patch: 1
+                    })
+                ),
+                (
+                    "packages/cli".to_owned(),
+                    VersionInfo::Production(SemverBase {
+                        major: 0,
+                        minor: 2,
+                        patch: 0
+                    })
+                ),
+            ])
+        );
+
+        // Tags of trees are skipped, and lightweight tags too when annotated ones are required
+        let tree = repo.repo.head().unwrap().peel_to_tree().unwrap();
+        repo.repo
+            .tag_lightweight("core-v2.0.0", tree.as_object(), false)
+            .unwrap();
+        repo.tag_annotated("core-cli-v0.3.0");
+        let config = GitflowConfig {
+            require_annotated_tags: true,
+            ..config
+        };
+        assert_eq!(
+            manifest_versions(&repo.repo, &config).unwrap(),
+            HashMap::from([(
+                "packages/cli".to_owned(),
+                VersionInfo::Production(SemverBase {
+                    major: 0,
+                    minor: 3,
+                    patch: 0
+                })
+            )])
+        );
+    }
+
+    #[test]
+    fn nth_latest_releases() {
+        let repo = TestRepo::new();
+        let first = repo.commit("initial");
+        repo.tag("v1.0.0");
+        let second = repo.commit("second");
+        repo.tag("v1.1.0");
+        repo.tag("v1.2.0-rc.1");
+        let third = repo.commit("third");
+        repo.tag("v1.2.0");
+
+        assert_eq!(
+            nth_latest_release(&repo.repo, 0, &GitflowConfig::default()).unwrap(),
+            Some((base(2), third))
+        );
+        assert_eq!(
+            nth_latest_release(&repo.repo, 1, &GitflowConfig::default()).unwrap(),
+            Some((base(1), second))
+        );
+        assert_eq!(
+            nth_latest_release(&repo.repo, 2, &GitflowConfig::default()).unwrap(),
+            Some((base(0), first))
+        );
+        assert_eq!(
+            nth_latest_release(&repo.repo, 3, &GitflowConfig::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn releases_ahead_of_build() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("second");
+        repo.tag("v1.1.0");
+        repo.commit("third");
+        repo.tag("v1.3.0-rc.1");
+        repo.tag("v1.2.0");
+        repo.commit("fourth");
+        repo.tag("v1.3.0");
+
+        let mut config = GitflowConfig::default();
+        assert_eq!(
+            releases_ahead(&repo.repo, &base(0), &config).unwrap(),
+            vec![base(1), base(2), base(3)]
+        );
+        assert_eq!(
+            releases_ahead(&repo.repo, &base(2), &config).unwrap(),
+            vec![base(3)]
+        );
+        assert!(releases_ahead(&repo.repo, &base(3), &config)
+            .unwrap()
+            .is_empty());
+
+        // Tags the configuration rules out aren't releases
+        config.tag_filter = Some(TagFilter::new(|name| name != "v1.3.0"));
+        assert_eq!(
+            releases_ahead(&repo.repo, &base(0), &config).unwrap(),
+            vec![base(1), base(2)]
+        );
+    }
+
+    #[test]
+    fn next_tags_on_release_branch() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        assert_eq!(
+            next_tags(&repo.repo, &GitflowConfig::default()).unwrap(),
+            NextTags {
+                production: None,
+                rc: None,
+                development_base: Some(base(1)),
+            }
+        );
+
+        repo.branch("release/v1.1.0");
+        repo.commit("fix");
+        assert_eq!(
+            next_tags(&repo.repo, &GitflowConfig::default()).unwrap(),
+            NextTags {
+                production: None,
+                rc: Some(SemverRC {
+                    base: base(1),
+                    rc: 1
+                }),
+                development_base: Some(base(2)),
+            }
+        );
+
+        repo.tag("v1.1.0-rc.1");
+        repo.commit("another fix");
+        assert_eq!(
+            next_tags(&repo.repo, &GitflowConfig::default()).unwrap(),
+            NextTags {
+                production: Some(base(1)),
+                rc: Some(SemverRC {
+                    base: base(1),
+                    rc: 2
+                }),
+                development_base: Some(base(2)),
+            }
+        );
+
+        repo.tag("v1.1.0");
+        let next = next_tags(&repo.repo, &GitflowConfig::default()).unwrap();
+        assert_eq!((next.production, next.rc), (None, None));
+    }
+}
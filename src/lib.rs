@@ -31,18 +31,90 @@
 //!
 //! TODO: Hotfix branch and how that works
 
-use git2::{Branch, Commit};
+mod branch;
+mod bundle;
+mod clock;
+#[cfg(feature = "color")]
+mod color;
+mod config;
+mod counter;
+mod error;
+mod explain;
+mod gitflow;
+mod github;
+mod graph;
+mod history;
+mod merge;
+mod notes;
+mod output;
+mod revoked;
+mod signature;
+#[cfg(feature = "json")]
+mod stamp;
+mod tags;
+mod tarball;
+#[cfg(test)]
+mod test_util;
+mod validate;
+
+pub use branch::{
+    all_branch_versions, base_from_branch, classify_branch, feature_prerelease_version,
+    looks_like_gitflow, target_branch_version, BranchClass, BranchVersions, BRANCH_IGNORE_FILE,
+};
+use branch::{local_branches, BranchIgnore};
+pub use bundle::{get_info_from_bundle, get_info_from_bundle_with_config};
+pub use clock::{Clock, FixedClock, SystemClock};
+#[cfg(feature = "color")]
+pub use color::{Color, Palette};
+pub use config::{
+    ChannelOverride, Codename, DevVersionScheme, DowngradeCheck, GitflowConfig, SubmoduleContext,
+    TagFilter,
+};
+pub use error::GitflowError;
+pub use explain::{explain, explain_with_sink, Explanation};
+pub use gitflow::Gitflow;
+pub use graph::{
+    base_branch, channel_transition, develop_position, is_orphan, is_release_only,
+    production_delta, refs_at_head, release_branch_point, required_refs, unpushed_commits,
+    DevelopPosition, ProductionDelta,
+};
+pub use history::{
+    changelog_range, is_version_bump_commit, manifest_versions, next_tags, nth_latest_release,
+    release_intervals, release_windows, releases_ahead, releases_containing, suggested_bump,
+    BumpKind, NextTags, ReleaseWindow, VERSION_FILE,
+};
+pub use merge::{
+    integration_style, project_develop_merge, project_develop_merge_info, projected_production,
+    IntegrationStyle,
+};
+pub use notes::{write_version_note, NoteConflict};
+pub use output::{ChannelSuffix, SeparatorConfig};
+pub use revoked::{is_revoked, RevocationMarker};
+pub use signature::production_tag_signer;
+#[cfg(feature = "json")]
+pub use stamp::{write_stamp, STAMP_FILE};
+pub use tags::{TagIndex, VersionTag};
+pub use tarball::VERSION_ENV_VAR;
+pub use validate::{
+    detect_ref_collisions, validate_gitflow, validate_proposed_tag, validate_rc_sequence,
+    GitflowViolation, ViolationKind,
+};
+
+use git2::Branch;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, path::Path};
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Copy, Clone, Hash)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, Hash)]
 pub struct SemverBase {
     pub major: u8,
     pub minor: u8,
     pub patch: u8,
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Copy, Clone, Hash)]
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Copy, Clone, Hash)]
 pub struct SemverRC {
     pub base: SemverBase,
     pub rc: u8,
@@ -64,12 +136,87 @@ pub enum VersionInfo {
     Local,
 }
 
+/// The release channel of a version, without any version numbers
+#[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum Channel {
+    Production,
+    Alpha,
+    Development,
+    Local,
+}
+
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug, Clone, Hash)]
 pub struct GitflowInfo {
     pub branch_name: String,
     pub version: VersionInfo,
     pub commit_hash: String,
+    /// Hash of the tree at HEAD. Unlike the commit hash, this only changes when the content
+    /// changes, so it suits cache keys that should survive amending a commit message.
+    pub tree_hash: String,
+    /// Committer time of the commit, in seconds since the Unix epoch
+    pub commit_time: i64,
     pub build_number: u64,
+    /// Sortable version of a development build, e.g. `v1.3.0-dev.42`, when the next release can
+    /// be determined. See [`VersionInfo::to_dev_version`].
+    pub dev_version: Option<String>,
+    /// Branch merged by the HEAD commit of a development build, taken from the merge commit
+    /// message. `None` if HEAD isn't a merge commit or its message isn't recognized.
+    pub merged_branch: Option<String>,
+    /// Pull request squash merged by the HEAD commit of a development build, such as `#12` for
+    /// `Add a login page (#12)`, as squash merges don't record the branch
+    pub merged_pull_request: Option<String>,
+    /// Name and pinned commit of each submodule as recorded by this repository, only populated
+    /// when [`GitflowConfig::submodule_pins`] is set
+    pub submodule_pins: Vec<(String, String)>,
+    /// Base branch a local build's branch was cut from, such as `develop` or a release branch.
+    /// See [`base_branch`].
+    pub base_branch: Option<String>,
+    /// Number of files tracked in the commit's tree, counting symlinks and submodules as one file
+    /// each. Only counted when [`GitflowConfig::tree_file_count`] is set, otherwise 0.
+    pub tree_file_count: usize,
+    /// Value of the build counter persisted in [`GitflowConfig::build_counter_file`], which
+    /// increases with every build regardless of the git history. `None` if no counter is kept.
+    pub build_counter: Option<u64>,
+    /// Author of the commit as `Name <email>`, who originally wrote the change
+    pub author: String,
+    /// Committer of the commit as `Name <email>`, who last applied it, such as by rebasing or
+    /// merging. The same as the author for commits that were never rewritten.
+    pub committer: String,
+    /// Whether HEAD's branch has diverged from its upstream, with commits on both sides that the
+    /// other lacks, as happens when pushed history is rebased or amended. Upstream based metrics
+    /// like [`unpushed_commits`] aren't meaningful then. Only set by
+    /// [`get_info_with_config`] and friends, which look at the branch HEAD is on.
+    pub history_rewritten: bool,
+    /// Release name of the version from [`GitflowConfig::codenames`], such as "Jammy". `None`
+    /// for development and local builds and versions no range names.
+    pub codename: Option<String>,
+}
+
+impl SemverBase {
+    /// Packs this version into a single integer for places like firmware headers that only have
+    /// room for a `u32`:
+    ///
+    /// | Bits  | Field |
+    /// |-------|-------|
+    /// | 31-24 | major |
+    /// | 23-16 | minor |
+    /// | 15-0  | patch |
+    ///
+    /// Packed versions compare the same way as the versions themselves.
+    pub fn to_u32(&self) -> u32 {
+        (self.major as u32) << 24 | (self.minor as u32) << 16 | self.patch as u32
+    }
+
+    /// Unpacks a version packed by [`SemverBase::to_u32`]. The layout has 16 bits for the patch
+    /// version but `patch` is a `u8`, so values with a patch above 255 are an error rather than
+    /// being truncated.
+    pub fn from_u32(packed: u32) -> Result<Self, std::num::TryFromIntError> {
+        Ok(Self {
+            major: (packed >> 24) as u8,
+            minor: (packed >> 16) as u8,
+            patch: (packed & 0xffff).try_into()?,
+        })
+    }
 }
 
 impl Display for SemverBase {
@@ -99,6 +246,34 @@ impl Display for VersionInfo {
     }
 }
 
+impl Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self {
+            Channel::Production => write!(f, "production"),
+            Channel::Alpha => write!(f, "alpha"),
+            Channel::Development => write!(f, "development"),
+            Channel::Local => write!(f, "local"),
+        }
+    }
+}
+
+impl std::str::FromStr for Channel {
+    type Err = GitflowError;
+
+    /// Parses a channel name case insensitively, accepting `prod` and `dev` as abbreviations
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "production" | "prod" => Ok(Channel::Production),
+            "alpha" => Ok(Channel::Alpha),
+            "development" | "dev" => Ok(Channel::Development),
+            "local" => Ok(Channel::Local),
+            _ => Err(GitflowError::UnknownChannel {
+                channel: s.trim().to_owned(),
+            }),
+        }
+    }
+}
+
 impl VersionInfo {
     pub fn get_semver(&self) -> Option<String> {
         match &self {
@@ -109,6 +284,22 @@ impl VersionInfo {
         }
     }
 
+    /// The version as a [`semver::Version`], with release candidates as the prerelease `rc.N`.
+    /// Development and local builds have no semver version.
+    pub fn to_semver(&self) -> Option<semver::Version> {
+        let version = |base: &SemverBase| {
+            semver::Version::new(base.major.into(), base.minor.into(), base.patch.into())
+        };
+        match &self {
+            VersionInfo::Production(base) => Some(version(base)),
+            VersionInfo::Alpha(rc) => Some(semver::Version {
+                pre: semver::Prerelease::new(&format!("rc.{}", rc.rc)).ok()?,
+                ..version(&rc.base)
+            }),
+            VersionInfo::Development | VersionInfo::Local => None,
+        }
+    }
+
     pub fn is_production(&self) -> bool {
         matches!(self, &VersionInfo::Production(_))
     }
@@ -116,17 +307,258 @@ impl VersionInfo {
     pub fn is_alpha(&self) -> bool {
         matches!(self, &VersionInfo::Alpha(_))
     }
+
+    /// Returns true if this version carries a semver pre-release identifier (such as `-rc.1`).
+    /// Development and local builds have no semver version at all, so they are not pre-releases.
+    pub fn is_prerelease(&self) -> bool {
+        match &self {
+            VersionInfo::Alpha(_) => true,
+            VersionInfo::Production(_) | VersionInfo::Development | VersionInfo::Local => false,
+        }
+    }
+
+    pub fn channel(&self) -> Channel {
+        match &self {
+            VersionInfo::Production(_) => Channel::Production,
+            VersionInfo::Alpha(_) => Channel::Alpha,
+            VersionInfo::Development => Channel::Development,
+            VersionInfo::Local => Channel::Local,
+        }
+    }
+
+    /// Short label of the maintenance line this version belongs to, for grouping builds in
+    /// dashboards: `<major>.<minor>.x` for production and alpha versions, such as `1.2.x` for both
+    /// `v1.2.3` and `v1.2.4-rc.1`, and `dev` or `local` for development and local builds
+    pub fn release_line_label(&self) -> String {
+        match &self {
+            VersionInfo::Production(base) | VersionInfo::Alpha(SemverRC { base, .. }) => {
+                format!("{}.{}.x", base.major, base.minor)
+            }
+            VersionInfo::Development => "dev".to_owned(),
+            VersionInfo::Local => "local".to_owned(),
+        }
+    }
+
+    /// Describes a build relative to the release it is working towards, as
+    /// `<next_base>-dev.<build>` for development builds and `<next_base>-local.<build>` for local
+    /// builds. `build` is normally [`GitflowInfo::build_number`], the number of commits in the
+    /// history of the build, so later builds of the same branch sort after earlier ones.
+    ///
+    /// Production and alpha versions already identify their release and are returned as is.
+    pub fn to_dev_version(&self, next_base: SemverBase, build: u64) -> String {
+        match &self {
+            VersionInfo::Production(semver) => semver.to_string(),
+            VersionInfo::Alpha(semver) => semver.to_string(),
+            VersionInfo::Development => format!("{}-dev.{}", next_base, build),
+            VersionInfo::Local => format!("{}-local.{}", next_base, build),
+        }
+    }
+
+    /// Formats this version as the upstream part of a Debian package version, such as `1.2.3` or
+    /// `1.2.3~rc4`. dpkg sorts `~` before anything, even the end of the version, so release
+    /// candidates sort before their release. Development and local builds have no version.
+    ///
+    /// Neither an epoch nor a Debian revision is included. Append the revision, such as `-1`, when
+    /// writing `debian/changelog`, and only prefix an epoch like `1:` if a package has to recover
+    /// from a version that was mistakenly released higher, as epochs can never be removed.
+    pub fn debian_version(&self) -> Option<String> {
+        match &self {
+            VersionInfo::Production(base) => {
+                Some(format!("{}.{}.{}", base.major, base.minor, base.patch))
+            }
+            VersionInfo::Alpha(rc) => Some(format!(
+                "{}.{}.{}~rc{}",
+                rc.base.major, rc.base.minor, rc.base.patch, rc.rc
+            )),
+            VersionInfo::Development | VersionInfo::Local => None,
+        }
+    }
+
+    /// Stability of the channel this version belongs to as a number, higher is more stable, for
+    /// sorting builds by stability:
+    ///
+    /// | Version       | Rank |
+    /// |---------------|------|
+    /// | `Production`  | 3    |
+    /// | `Alpha`       | 2    |
+    /// | `Development` | 1    |
+    /// | `Local`       | 0    |
+    ///
+    /// These ranks are part of the public API and will not change.
+    pub fn channel_rank(&self) -> u8 {
+        match &self {
+            VersionInfo::Production(_) => 3,
+            VersionInfo::Alpha(_) => 2,
+            VersionInfo::Development => 1,
+            VersionInfo::Local => 0,
+        }
+    }
+}
+
+/// Versions are ordered by the stability of their channel first, so that any production version
+/// is greater than any alpha version, which is greater than development, which is greater than
+/// local. Versions in the same channel are ordered by their semver precedence.
+impl Ord for VersionInfo {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.channel_rank()
+            .cmp(&other.channel_rank())
+            .then_with(|| match (self, other) {
+                (VersionInfo::Production(a), VersionInfo::Production(b)) => a.cmp(b),
+                (VersionInfo::Alpha(a), VersionInfo::Alpha(b)) => a.cmp(b),
+                _ => std::cmp::Ordering::Equal,
+            })
+    }
 }
 
-fn parse_semver(semver: &str) -> Result<VersionInfo, Box<dyn std::error::Error>> {
-    if !semver.starts_with('v') {
-        return Err("Semver must start with a v".into());
+impl PartialOrd for VersionInfo {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
     }
-    let semver = &semver[1..];
-    let version = semver::Version::parse(semver)?;
-    if !version.build.is_empty() {
+}
+
+impl GitflowInfo {
+    /// Whether the version of this build meets `requirement`, such as a client's `^1.2` minimum
+    /// during protocol negotiation. Matching follows the semver crate, so a release candidate only
+    /// satisfies requirements that name a prerelease of the same version. Development and local
+    /// builds have no known version and never satisfy a requirement.
+    pub fn satisfies(&self, requirement: &semver::VersionReq) -> bool {
+        self.version
+            .to_semver()
+            .is_some_and(|version| requirement.matches(&version))
+    }
+
+    /// The semver version of this build with the build number as build metadata, e.g.
+    /// `v1.2.3+build.57`. Parsing this with [`GitflowInfo::from_version_string`] gives back the
+    /// version and build number. Development and local builds have no semver version.
+    pub fn to_semver_with_build(&self) -> Option<String> {
+        Some(format!(
+            "{}+build.{}",
+            self.version.get_semver()?,
+            self.build_number
+        ))
+    }
+
+    /// Constructs an info from a version string like `v1.2.3+build.57`, for consumers that only
+    /// have the version string of a build. The build number is recovered from the build metadata
+    /// as described in [`parse_semver_with_build`] and is 0 if there is none. Everything that
+    /// can't be known from the string, like the branch and commit, is left empty.
+    pub fn from_version_string(version: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let (version, build_number) = parse_semver_with_build(version)?;
+        Ok(Self::without_repo(version, build_number.unwrap_or(0)))
+    }
+
+    /// Constructs an info for a build whose repository isn't available, leaving everything but
+    /// the version and build number empty
+    pub(crate) fn without_repo(version: VersionInfo, build_number: u64) -> Self {
+        Self {
+            branch_name: String::new(),
+            version,
+            commit_hash: String::new(),
+            tree_hash: String::new(),
+            commit_time: 0,
+            build_number,
+            dev_version: None,
+            merged_branch: None,
+            merged_pull_request: None,
+            submodule_pins: Vec::new(),
+            base_branch: None,
+            tree_file_count: 0,
+            build_counter: None,
+            author: String::new(),
+            committer: String::new(),
+            history_rewritten: false,
+            codename: None,
+        }
+    }
+
+    /// Reduces the infos of every crate in a workspace to a single info representing the
+    /// workspace as a whole.
+    ///
+    /// A workspace is only as stable as its least stable crate, so the result is taken from the
+    /// lowest channel present among `infos` (e.g. a workspace with one `Local` crate is `Local`).
+    /// Within that channel the highest version wins according to the [`Ord`] impl of
+    /// [`VersionInfo`], and ties are broken by the highest build number.
+    ///
+    /// Returns `None` if `infos` is empty.
+    pub fn workspace_version(infos: &[GitflowInfo]) -> Option<GitflowInfo> {
+        let lowest = infos.iter().map(|info| info.version.channel_rank()).min()?;
+        infos
+            .iter()
+            .filter(|info| info.version.channel_rank() == lowest)
+            .max_by_key(|info| (info.version, info.build_number))
+            .cloned()
+    }
+
+    /// Returns true if deploying `next` in place of this build is a legal step forward in a
+    /// release pipeline. The allowed transitions are:
+    /// - From development: a development build with the same or a higher build number, or any
+    ///   alpha or production build
+    /// - From alpha `X-rc.N`: alpha `X-rc.M` with `M >= N`, an alpha of a higher base, or
+    ///   production `X` or higher
+    /// - From production `X`: production `X` or higher, or an alpha of a higher base
+    ///
+    /// Everything else is rejected, in particular going back to a lower version, demoting alpha or
+    /// production builds to development builds, and local builds on either side, which are never
+    /// deployed.
+    pub fn is_valid_transition_to(&self, next: &GitflowInfo) -> bool {
+        use VersionInfo::*;
+        match (self.version, next.version) {
+            (Local, _) | (_, Local) => false,
+            (Development, Development) => next.build_number >= self.build_number,
+            (Development, _) => true,
+            (Alpha(from), Alpha(to)) => {
+                to.base > from.base || (to.base == from.base && to.rc >= from.rc)
+            }
+            (Alpha(from), Production(to)) => to >= from.base,
+            (Production(from), Production(to)) => to >= from,
+            (Production(from), Alpha(to)) => to.base > from,
+            (Alpha(_) | Production(_), Development) => false,
+        }
+    }
+}
+
+/// Parses a version in the form `vX.Y.Z` or `vX.Y.Z-rc.W`.
+///
+/// Never panics, any input that isn't a supported version is reported as an error.
+pub fn parse_semver(semver: &str) -> Result<VersionInfo, Box<dyn std::error::Error>> {
+    let (version, build) = parse_semver_with_metadata(semver)?;
+    if !build.is_empty() {
         return Err("Semver must not contain a build identifier".into());
     }
+    Ok(version)
+}
+
+/// Parses a version like [`parse_semver`], but allows build metadata and extracts a build number
+/// from it. The build number is the numeric identifier after a `build` identifier, so
+/// `v1.2.3+build.57` gives build 57. Versions without metadata, or whose metadata doesn't contain
+/// a numeric `build.N`, give no build number.
+pub fn parse_semver_with_build(
+    semver: &str,
+) -> Result<(VersionInfo, Option<u64>), Box<dyn std::error::Error>> {
+    let (version, build) = parse_semver_with_metadata(semver)?;
+    let mut identifiers = build.split('.');
+    let mut build_number = None;
+    while let Some(identifier) = identifiers.next() {
+        if identifier == "build" {
+            build_number = identifiers
+                .next()
+                .filter(|n| n.bytes().all(|b| b.is_ascii_digit()))
+                .and_then(|n| n.parse().ok());
+            break;
+        }
+    }
+    Ok((version, build_number))
+}
+
+/// Parses a version, returning the build metadata unchecked
+fn parse_semver_with_metadata(
+    semver: &str,
+) -> Result<(VersionInfo, String), Box<dyn std::error::Error>> {
+    let semver = semver
+        .strip_prefix('v')
+        .ok_or("Semver must start with a v")?;
+    let version = semver::Version::parse(semver)?;
+    let build = version.build.as_str().to_owned();
 
     let pre = version.pre.as_str();
     let base = SemverBase {
@@ -135,26 +567,213 @@ fn parse_semver(semver: &str) -> Result<VersionInfo, Box<dyn std::error::Error>>
         patch: version.patch.try_into()?,
     };
     if pre.is_empty() {
-        Ok(VersionInfo::Production(base))
+        return Ok((VersionInfo::Production(base), build));
+    }
+
+    let parts: Vec<&str> = pre.split('.').collect();
+    match parts.as_slice() {
+        ["rc", rc] => {
+            // Only plain decimal digits, `u8::from_str` would also accept a leading `+`
+            if !rc.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(format!("Invalid release candidate number: {rc}").into());
+            }
+            let rc: u8 = rc.parse()?;
+            Ok((VersionInfo::Alpha(SemverRC { base, rc }), build))
+        }
+        ["rc", ..] => Err("Expected rc.W at end of version".into()),
+        [first, ..] => Err(format!("Unsupported prerelease: {first}").into()),
+        [] => Err("Expected rc.W at end of version".into()),
+    }
+}
+
+pub fn get_info_from_path(path: &Path) -> Result<GitflowInfo, GitflowError> {
+    get_info_with_config(path, &GitflowConfig::default())
+}
+
+/// Determines the version of HEAD in the repository at `path` using `config`. With the `json`
+/// feature, a directory that isn't a repository but has a `.gitflow-stamp.json` version stamp,
+/// such as a packaged crate, gives the stamped version instead. Failing that, a source tarball
+/// can be versioned with [`GitflowConfig::tarball_fallback`].
+pub fn get_info_with_config(
+    path: &Path,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let resolution = match config.timeout {
+        Some(timeout) => {
+            let (path, config) = (path.to_owned(), config.clone());
+            resolve_with_timeout(timeout, move || resolve(&path, &config))
+        }
+        None => resolve(path, config),
+    };
+    apply_fallback(count_build(resolution, config), config)
+}
+
+/// Determines the version of HEAD in an already opened repository exactly like
+/// [`get_info_with_config`] does for a path, for [`Gitflow::info`]. The
+/// [`GitflowConfig::build_counter_file`] is relative to the working tree, or to the git directory
+/// of a bare repository.
+pub(crate) fn get_info_with_repo(
+    repo: &git2::Repository,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let dir = repo.workdir().unwrap_or_else(|| repo.path()).to_owned();
+    let resolution = match config.timeout {
+        Some(timeout) => {
+            // The repository can't be shared with the worker, which reopens it
+            let (git_dir, config) = (repo.path().to_owned(), config.clone());
+            resolve_with_timeout(timeout, move || {
+                resolve_repo(&git2::Repository::open(git_dir)?, dir, &config)
+            })
+        }
+        None => resolve_repo(repo, dir, config),
+    };
+    apply_fallback(count_build(resolution, config), config)
+}
+
+/// Version resolved from a repository along with the directory its
+/// [`GitflowConfig::build_counter_file`] is relative to, or from elsewhere without one
+type Resolution = (GitflowInfo, Option<PathBuf>);
+
+/// Increments the build counter of a version resolved from a repository. This runs on the
+/// caller's thread once the resolution is in, so a resolution abandoned by
+/// [`GitflowConfig::timeout`] never takes a counter value.
+fn count_build(
+    resolution: Result<Resolution, GitflowError>,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let (mut info, dir) = resolution?;
+    if let (Some(dir), Some(file)) = (dir, &config.build_counter_file) {
+        info.build_counter = Some(counter::increment(&dir.join(file), config.dry_run)?);
+    }
+    Ok(info)
+}
+
+/// Replaces a failed resolution with [`GitflowConfig::fallback_version`] unless in
+/// [`GitflowConfig::strict`] mode, and fills in the codename
+fn apply_fallback(
+    info: Result<GitflowInfo, GitflowError>,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let mut info = match (info, config.fallback_version) {
+        (Err(err), Some(fallback)) if !config.strict => {
+            #[cfg(feature = "log")]
+            log::warn!("Using fallback version {}: {}", fallback, err);
+            #[cfg(not(feature = "log"))]
+            let _ = err;
+            GitflowInfo::without_repo(fallback, 0)
+        }
+        (info, _) => info?,
+    };
+    info.codename = config.codename(&info.version);
+    Ok(info)
+}
+
+/// Runs `resolve` on a separate thread, giving up with [`GitflowError::Timeout`] if it isn't done
+/// within `timeout`. The abandoned thread isn't interrupted, it runs to completion in the
+/// background.
+fn resolve_with_timeout<T: Send + 'static>(
+    timeout: std::time::Duration,
+    resolve: impl FnOnce() -> Result<T, GitflowError> + Send + 'static,
+) -> Result<T, GitflowError> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let worker = std::thread::spawn(move || {
+        let _ = sender.send(resolve());
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(info) => info,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(GitflowError::Timeout { timeout }),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("resolution finished without sending a result"),
+        },
+    }
+}
+
+/// Resolves the version for [`get_info_with_config`], before any fallback is applied
+fn resolve(path: &Path, config: &GitflowConfig) -> Result<Resolution, GitflowError> {
+    if let Some(info) = github_fast_path(config) {
+        return Ok((info, None));
+    }
+    match open_repository(path, config) {
+        Ok(repo) => resolve_repo(&repo, path.to_owned(), config),
+        Err(err) if err.code() == git2::ErrorCode::NotFound => {
+            #[cfg(feature = "json")]
+            if let Some(info) = stamp::read_stamp(path)? {
+                return Ok((info, None));
+            }
+            if config.tarball_fallback {
+                if let Some(info) = tarball::tarball_info(path, config)? {
+                    return Ok((info, None));
+                }
+            }
+            Err(err.into())
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Resolves the version of an opened repository, before any fallback is applied, keeping the
+/// build counter relative to `dir`
+fn resolve_repo(
+    repo: &git2::Repository,
+    dir: PathBuf,
+    config: &GitflowConfig,
+) -> Result<Resolution, GitflowError> {
+    if let Some(info) = github_fast_path(config) {
+        return Ok((info, None));
+    }
+    Ok((get_info_for_repo(repo, config)?, Some(dir)))
+}
+
+/// Reads the version from the GitHub Actions environment if
+/// [`GitflowConfig::github_ref_fast_path`] is set
+fn github_fast_path(config: &GitflowConfig) -> Option<GitflowInfo> {
+    if config.github_ref_fast_path {
+        github::github_tag_info(config)
     } else {
-        let mut parts = pre.split('.');
-        let first = parts.next().unwrap();
-        let second = parts.next().ok_or("Expected rc.W at end of version")?;
+        None
+    }
+}
 
-        if first == "rc" {
-            let rc: u8 = second.parse()?;
-            Ok(VersionInfo::Alpha(SemverRC { base, rc }))
-        } else {
-            Err(format!("Unsupported prerelease: {first}").into())
+/// Opens the repository at `path`, or the superproject containing it if `path` is a submodule
+/// and [`GitflowConfig::submodule_context`] asks for it
+pub(crate) fn open_repository(
+    path: &Path,
+    config: &GitflowConfig,
+) -> Result<git2::Repository, git2::Error> {
+    let repo = git2::Repository::open(path)?;
+    if config.submodule_context == SubmoduleContext::Superproject {
+        if let Some(superproject) = superproject(&repo) {
+            return Ok(superproject);
         }
     }
+    Ok(repo)
+}
+
+/// Finds the repository that has `repo` checked out as one of its submodules
+fn superproject(repo: &git2::Repository) -> Option<git2::Repository> {
+    let workdir = repo.workdir()?;
+    let parent = git2::Repository::discover(workdir.parent()?).ok()?;
+    let path = workdir.strip_prefix(parent.workdir()?).ok()?;
+    let is_submodule = parent
+        .submodules()
+        .ok()?
+        .iter()
+        .any(|submodule| submodule.path() == path);
+    if is_submodule {
+        Some(parent)
+    } else {
+        None
+    }
 }
 
-pub fn get_info_from_path(path: &Path) -> Result<GitflowInfo, Box<dyn std::error::Error>> {
-    let repo = git2::Repository::open(&path)?;
+/// Determines the version of HEAD in an already opened repository
+pub(crate) fn get_info_for_repo(
+    repo: &git2::Repository,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
     let head = repo.head()?;
     let head_commit = head.peel_to_commit()?;
-    let commit_hash = hex::encode(head_commit.id().as_bytes());
 
     let branches: Vec<Branch> = repo
         .branches(None)?
@@ -167,37 +786,679 @@ pub fn get_info_from_path(path: &Path) -> Result<GitflowInfo, Box<dyn std::error
         .collect();
 
     if branches.len() > 1 {
-        return Err("Commit on too many branches!".into());
+        let branches = branches
+            .iter()
+            .filter_map(|branch| branch.name().ok().flatten().map(str::to_owned))
+            .collect();
+        return Err(GitflowError::TooManyBranches { branches });
+    }
+    let branch = match branches.into_iter().next() {
+        Some(branch) => branch,
+        None => return detached_rc_info(repo, &head_commit, config),
+    };
+    let branch_name = branch.name()?.unwrap_or_default();
+
+    let env_channel = std::env::var(CHANNEL_ENV_VAR).ok();
+    let file_channel = match repo.workdir() {
+        Some(workdir) => read_channel_file(workdir)?,
+        None => None,
+    };
+    let channel = channel_override(env_channel.as_deref(), file_channel.as_deref())?;
+    let mut info = info_for_commit(repo, &head_commit, branch_name, channel, config)?;
+    if config.submodule_pins {
+        info.submodule_pins = read_submodule_pins(repo, config)?;
     }
-    if branches.is_empty() {
-        return Err("Commit {commit_hash} on no branch".into());
+    info.history_rewritten = graph::history_rewritten(repo)?;
+    Ok(info)
+}
+
+/// Determines the version of a detached HEAD from a release candidate tag on it, so a tagged rc
+/// whose release branch was deleted is still an alpha build. The info has no branch name. Fails
+/// with [`GitflowError::NoBranch`] if the commit has no release candidate tag.
+fn detached_rc_info(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let tag = tags.at(commit.id()).find_map(|tag| match tag.version {
+        VersionInfo::Alpha(rc) => Some((tag.name.clone(), rc)),
+        _ => None,
+    });
+    let (name, rc) = match tag {
+        Some(tag) => tag,
+        None => {
+            return Err(GitflowError::NoBranch {
+                commit: format_hash(commit.id(), config),
+            })
+        }
+    };
+    // Version the commit as if it were on the release branch of the tag, whatever the naming
+    let mut config = config.clone();
+    config.branch_overrides.insert(
+        name.clone(),
+        ChannelOverride {
+            channel: Channel::Alpha,
+            base: Some(rc.base),
+        },
+    );
+    let mut info = info_for_commit(repo, commit, &name, None, &config)?;
+    info.branch_name = String::new();
+    if config.submodule_pins {
+        info.submodule_pins = read_submodule_pins(repo, &config)?;
     }
-    let branch = branches.into_iter().next().unwrap();
-    let branch_name = branch.name()?.unwrap();
+    Ok(info)
+}
 
-    // Count the number of parent commits
-    let build_number: usize = count_parents(&head_commit);
-    fn count_parents(commit: &Commit) -> usize {
-        let mut count = 0;
-        for commit in commit.parents() {
-            count += count_parents(&commit);
+/// Determines the version of the branch `refname` points to, such as `refs/heads/release/v1.2.3`,
+/// without looking at HEAD. This works in bare repositories, so server side hooks can version
+/// the refs being pushed. Short names like `develop` are accepted too, and unlike git they resolve
+/// to a branch before a tag of the same name, see [`detect_ref_collisions`].
+///
+/// The channel can still be overridden with [`CHANNEL_ENV_VAR`], but the [`CHANNEL_FILE`] isn't
+/// read as it belongs to the checked out commit. Submodule pins are read from the commit's tree.
+pub fn get_info_for_ref(
+    repo: &git2::Repository,
+    refname: &str,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let reference = repo
+        .find_reference(&format!("refs/heads/{}", refname))
+        .or_else(|_| repo.resolve_reference_from_short_name(refname))?;
+    let commit = reference.peel_to_commit()?;
+    let branch_name = match reference.name() {
+        Some(name) if reference.is_branch() => name.trim_start_matches("refs/heads/"),
+        _ => {
+            return Err(GitflowError::NoBranch {
+                commit: format_hash(commit.id(), config),
+            })
+        }
+    };
+    get_info_for_commit(repo, commit.id(), branch_name, config)
+}
+
+/// Determines the version of the commit a deployment pointer such as `refs/deploy/production`
+/// leads to, following symbolic refs through any chain of others, to answer what is currently
+/// deployed. Short names are looked up like git does. A pointer that ends at a branch is versioned
+/// like [`get_info_for_ref`]. Any other ref, such as a tag or a ref pointing straight at a commit,
+/// is versioned by the version tags on the commit, a release taking precedence over a release
+/// candidate, and the info has no branch name. Fails with [`GitflowError::NoBranch`] if it ends at
+/// a commit with no version tag.
+pub fn get_info_for_symbolic_ref(
+    repo: &git2::Repository,
+    refname: &str,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let reference = repo
+        .find_reference(refname)
+        .or_else(|_| repo.resolve_reference_from_short_name(refname))?
+        .resolve()?;
+    let commit = reference.peel_to_commit()?;
+    if reference.is_branch() {
+        if let Some(name) = reference.name() {
+            let name = name.trim_start_matches("refs/heads/");
+            return get_info_for_commit(repo, commit.id(), name, config);
+        }
+    }
+    let tags = TagIndex::new(repo, config)?;
+    let tag = tags
+        .at(commit.id())
+        .filter(|tag| {
+            matches!(
+                tag.version,
+                VersionInfo::Production(_) | VersionInfo::Alpha(_)
+            )
+        })
+        .max_by_key(|tag| tag.version);
+    let (name, channel, base) = match tag.map(|tag| (tag.name.clone(), tag.version)) {
+        Some((name, VersionInfo::Production(base))) => (name, Channel::Production, base),
+        Some((name, VersionInfo::Alpha(rc))) => (name, Channel::Alpha, rc.base),
+        _ => {
+            return Err(GitflowError::NoBranch {
+                commit: format_hash(commit.id(), config),
+            })
         }
-        count
+    };
+    // Version the commit as if it were on a branch of the tag's channel, whatever the naming
+    let mut config = config.clone();
+    config.branch_overrides.insert(
+        name.clone(),
+        ChannelOverride {
+            channel,
+            base: Some(base),
+        },
+    );
+    let mut info = info_for_commit(repo, &commit, &name, None, &config)?;
+    info.branch_name = String::new();
+    if config.submodule_pins {
+        info.submodule_pins = tree_submodule_pins(&commit.tree()?, &config)?;
+    }
+    Ok(info)
+}
+
+/// Determines the version `commit` has on the branch named `branch_name`, whether or not the
+/// branch points at it. This suits pre-receive hooks, where the pushed commit isn't on the
+/// branch yet. Like [`get_info_for_ref`], the [`CHANNEL_FILE`] isn't read and submodule pins come
+/// from the commit's tree.
+pub fn get_info_for_commit(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    branch_name: &str,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let commit = repo.find_commit(commit)?;
+    let channel = channel_override(std::env::var(CHANNEL_ENV_VAR).ok().as_deref(), None)?;
+    let mut info = info_for_commit(repo, &commit, branch_name, channel, config)?;
+    if config.submodule_pins {
+        info.submodule_pins = tree_submodule_pins(&commit.tree()?, config)?;
     }
+    Ok(info)
+}
+
+/// Determines everything but the submodule pins of the info for `commit` on `branch_name`
+fn info_for_commit(
+    repo: &git2::Repository,
+    commit: &git2::Commit,
+    branch_name: &str,
+    channel: Option<Channel>,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let commit_hash = format_hash(commit.id(), config);
+    let tree_hash = format_hash(commit.tree_id(), config);
+
+    let tags = TagIndex::new(repo, config)?;
+    let version = resolve_version(repo, branch_name, commit.id(), channel, &tags, config)?;
+
+    let build_number = build_number(repo, commit.id(), version, config)?;
+
+    let dev_version = dev_version(
+        repo,
+        &tags,
+        version,
+        build_number,
+        commit.time().seconds(),
+        config,
+    )?;
+
+    let (merged_branch, merged_pull_request) = match version {
+        VersionInfo::Development if commit.parent_count() > 1 => {
+            (commit.message().and_then(merge::merged_branch_name), None)
+        }
+        VersionInfo::Development => (None, commit.message().and_then(merge::squash_pr_reference)),
+        _ => (None, None),
+    };
+
+    let base_branch = match version {
+        VersionInfo::Local => graph::base_branch(repo, commit.id(), config)?,
+        _ => None,
+    };
+
+    let tree_file_count = if config.tree_file_count {
+        count_tree_files(&commit.tree()?)?
+    } else {
+        0
+    };
 
-    let output = "";
     Ok(GitflowInfo {
         branch_name: branch_name.to_owned(),
-        version: parse_semver(output)
-            .unwrap_or_else(|_| panic!("failed to parse semver: {}", output)),
+        version,
         commit_hash,
-        build_number: build_number as u64,
+        tree_hash,
+        commit_time: commit.time().seconds(),
+        build_number,
+        dev_version,
+        merged_branch,
+        merged_pull_request,
+        submodule_pins: Vec::new(),
+        base_branch,
+        tree_file_count,
+        build_counter: None,
+        author: signature_name(&commit.author()),
+        committer: signature_name(&commit.committer()),
+        history_rewritten: false,
+        codename: config.codename(&version),
+    })
+}
+
+/// Counts the build number of `commit`, the number of commits in its history. With
+/// [`GitflowConfig::count_integrations`], development builds of a develop branch that merges with
+/// merge commits count its first parent history instead.
+fn build_number(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    version: VersionInfo,
+    config: &GitflowConfig,
+) -> Result<u64, GitflowError> {
+    let count = match version {
+        VersionInfo::Development
+            if config.count_integrations
+                && merge::integration_style(repo, config)?
+                    == Some(IntegrationStyle::MergeCommit) =>
+        {
+            graph::count_first_parent_history(repo, commit)?
+        }
+        _ => graph::count_history(repo, commit, config)?,
+    };
+    Ok(count as u64)
+}
+
+/// Shortest abbreviated hash git accepts, the lower bound of [`GitflowConfig::hash_length`]
+const MIN_HASH_LENGTH: usize = 4;
+
+/// Formats an object id as lowercase hex, abbreviated to [`GitflowConfig::hash_length`]. Every
+/// hash the crate emits, in a [`GitflowInfo`], an error, or a query result, goes through here or
+/// [`abbreviate_hash`] so they share one representation, with [`short_hash`] shortening them
+/// further for human readable text.
+pub(crate) fn format_hash(id: git2::Oid, config: &GitflowConfig) -> String {
+    abbreviate_hash(hex::encode(id.as_bytes()), config)
+}
+
+/// Abbreviates a lowercase hex hash that didn't come from the repository, such as one read from
+/// the environment, to [`GitflowConfig::hash_length`] like [`format_hash`]
+pub(crate) fn abbreviate_hash(mut hex: String, config: &GitflowConfig) -> String {
+    if let Some(length) = config.hash_length {
+        hex.truncate(length.max(MIN_HASH_LENGTH));
+    }
+    hex
+}
+
+/// Length of the hashes in human readable text such as banners, the default of `git log --oneline`
+const SHORT_HASH_LENGTH: usize = 7;
+
+/// Shortens a hash already formatted by [`format_hash`] to [`SHORT_HASH_LENGTH`] characters for
+/// human readable text, keeping it as is if [`GitflowConfig::hash_length`] made it shorter
+pub(crate) fn short_hash(hash: &str) -> &str {
+    hash.get(..SHORT_HASH_LENGTH).unwrap_or(hash)
+}
+
+/// Formats a commit signature as `Name <email>`, replacing invalid UTF-8
+fn signature_name(signature: &git2::Signature) -> String {
+    format!(
+        "{} <{}>",
+        String::from_utf8_lossy(signature.name_bytes()),
+        String::from_utf8_lossy(signature.email_bytes())
+    )
+}
+
+/// Counts the blobs and gitlinks in `tree` and its subtrees. Symlinks are blobs and submodules are
+/// gitlinks, so neither is followed.
+fn count_tree_files(tree: &git2::Tree) -> Result<usize, GitflowError> {
+    let mut count = 0;
+    tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+        if matches!(
+            entry.kind(),
+            Some(git2::ObjectType::Blob | git2::ObjectType::Commit)
+        ) {
+            count += 1;
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+    Ok(count)
+}
+
+/// Reads the commit each submodule is pinned to from the gitlinks in `tree`, named by their path
+fn tree_submodule_pins(
+    tree: &git2::Tree,
+    config: &GitflowConfig,
+) -> Result<Vec<(String, String)>, GitflowError> {
+    let mut pins = Vec::new();
+    tree.walk(git2::TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(git2::ObjectType::Commit) {
+            let path = format!("{}{}", dir, entry.name().unwrap_or_default());
+            pins.push((path, format_hash(entry.id(), config)));
+        }
+        git2::TreeWalkResult::Ok
+    })?;
+    Ok(pins)
+}
+
+/// Reads the commit each submodule is pinned to from the index, falling back to the HEAD tree.
+/// This is the superproject's view of the pins, so it works for submodules that were never
+/// initialized or cloned.
+fn read_submodule_pins(
+    repo: &git2::Repository,
+    config: &GitflowConfig,
+) -> Result<Vec<(String, String)>, GitflowError> {
+    let mut pins = Vec::new();
+    for submodule in repo.submodules()? {
+        let name = match submodule.name() {
+            Some(name) => name.to_owned(),
+            None => submodule.path().to_string_lossy().into_owned(),
+        };
+        if let Some(id) = submodule.index_id().or_else(|| submodule.head_id()) {
+            pins.push((name, format_hash(id, config)));
+        }
+    }
+    Ok(pins)
+}
+
+/// Formats the [`GitflowInfo::dev_version`] of a build of `version` committed at `time` as
+/// [`GitflowConfig::dev_version_scheme`] says, `None` unless it is a development build
+pub(crate) fn dev_version(
+    repo: &git2::Repository,
+    tags: &TagIndex,
+    version: VersionInfo,
+    build_number: u64,
+    time: i64,
+    config: &GitflowConfig,
+) -> Result<Option<String>, GitflowError> {
+    Ok(match (version, config.dev_version_scheme) {
+        (VersionInfo::Development, DevVersionScheme::Semver) => {
+            next_development_base(repo, tags, config)?
+                .map(|next_base| version.to_dev_version(next_base, build_number))
+        }
+        (VersionInfo::Development, DevVersionScheme::Calendar) => {
+            let (year, month, day) = clock::utc_date(time);
+            Some(format!(
+                "{}.{:02}.{:02}-dev.{}",
+                year, month, day, build_number
+            ))
+        }
+        _ => None,
     })
 }
 
+/// Determines the release develop is working towards, which is the minor version after the
+/// highest version that has been released or branched for release
+pub(crate) fn next_development_base(
+    repo: &git2::Repository,
+    tags: &TagIndex,
+    config: &GitflowConfig,
+) -> Result<Option<SemverBase>, GitflowError> {
+    let mut highest = tags
+        .tags()
+        .iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some(base),
+            VersionInfo::Alpha(rc) => Some(rc.base),
+            _ => None,
+        })
+        .max();
+    for (name, _) in local_branches(repo)? {
+        if let BranchClass::Release(base) = classify_branch(&name, config) {
+            highest = highest.max(Some(base));
+        }
+    }
+    Ok(highest.and_then(|base| {
+        Some(SemverBase {
+            major: base.major,
+            minor: base.minor.checked_add(1)?,
+            patch: 0,
+        })
+    }))
+}
+
+/// Determines the version of `commit` on the branch named `branch_name`. If `channel` is given and
+/// differs from the branch's own channel, the version is resolved as if the commit were on a
+/// branch of that channel instead.
+pub(crate) fn resolve_version(
+    repo: &git2::Repository,
+    branch_name: &str,
+    commit: git2::Oid,
+    channel: Option<Channel>,
+    tags: &TagIndex,
+    config: &GitflowConfig,
+) -> Result<VersionInfo, GitflowError> {
+    let entry = config.branch_overrides.get(branch_name);
+    let mut class = match entry {
+        Some(ChannelOverride {
+            channel: Channel::Alpha,
+            base: Some(base),
+        }) => BranchClass::Release(*base),
+        Some(entry) => class_for_channel(entry.channel, tags)?,
+        None if BranchIgnore::read(repo)?.ignores(branch_name) => BranchClass::Feature,
+        None => classify_branch(branch_name, config),
+    };
+    if entry.is_none()
+        && !matches!(class, BranchClass::Develop | BranchClass::Production)
+        && graph::is_orphan(repo, commit, config)?
+    {
+        // Without shared history there are no tags or releases to version against
+        class = BranchClass::Feature;
+    }
+    if let Some(channel) = channel {
+        if channel != class_channel(class) {
+            class = class_for_channel(channel, tags)?;
+        }
+    }
+    if let (
+        Some(ChannelOverride {
+            channel: Channel::Production,
+            base: Some(base),
+        }),
+        BranchClass::Production,
+    ) = (entry, class)
+    {
+        return Ok(VersionInfo::Production(*base));
+    }
+    if let BranchClass::Release(release) = class {
+        check_release_downgrade(branch_name, release, tags, config)?;
+    }
+    version_for_class(repo, class, branch_name, commit, tags, config)
+}
+
+/// Environment variable that overrides the channel derived from the branch name
+pub const CHANNEL_ENV_VAR: &str = "GITFLOW_CHANNEL";
+
+/// File at the root of the working tree whose contents override the channel derived from the
+/// branch name
+pub const CHANNEL_FILE: &str = ".version-channel";
+
+/// Reads the contents of the channel file in `workdir`, if it exists
+pub(crate) fn read_channel_file(workdir: &Path) -> Result<Option<String>, GitflowError> {
+    match std::fs::read_to_string(workdir.join(CHANNEL_FILE)) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(GitflowError::Io(err)),
+    }
+}
+
+/// Picks the channel override to apply, if any. An explicit override from the environment takes
+/// precedence over the channel file, which takes precedence over the branch name.
+fn channel_override(
+    env: Option<&str>,
+    file: Option<&str>,
+) -> Result<Option<Channel>, GitflowError> {
+    env.or(file).map(str::parse).transpose()
+}
+
+/// The channel that versions on a branch of class `class` belong to
+fn class_channel(class: BranchClass) -> Channel {
+    match class {
+        BranchClass::Production => Channel::Production,
+        BranchClass::Release(_) | BranchClass::Hotfix(_) | BranchClass::Train(_) => Channel::Alpha,
+        BranchClass::Develop => Channel::Development,
+        BranchClass::Feature => Channel::Local,
+    }
+}
+
+/// Picks the branch class whose versions belong to `channel`, so that an overridden channel is
+/// resolved the same way as a branch with a matching name. Alpha versions continue the release
+/// candidates of the highest version that has any.
+fn class_for_channel(channel: Channel, tags: &TagIndex) -> Result<BranchClass, GitflowError> {
+    Ok(match channel {
+        Channel::Production => BranchClass::Production,
+        Channel::Alpha => {
+            let base = tags
+                .tags()
+                .iter()
+                .filter_map(|tag| match tag.version {
+                    VersionInfo::Alpha(rc) => Some(rc.base),
+                    _ => None,
+                })
+                .max();
+            match base {
+                Some(base) => BranchClass::Release(base),
+                None => return Err(GitflowError::NoReleaseCandidate),
+            }
+        }
+        Channel::Development => BranchClass::Develop,
+        Channel::Local => BranchClass::Feature,
+    })
+}
+
+/// Determines the version of `commit` on a branch of class `class`
+fn version_for_class(
+    repo: &git2::Repository,
+    class: BranchClass,
+    branch_name: &str,
+    commit: git2::Oid,
+    tags: &TagIndex,
+    config: &GitflowConfig,
+) -> Result<VersionInfo, GitflowError> {
+    Ok(match class {
+        BranchClass::Production => {
+            let at_commit: Vec<_> = tags
+                .at(commit)
+                .filter(|tag| matches!(tag.version, VersionInfo::Production(_)))
+                .collect();
+            check_unambiguous(commit, &at_commit, config)?;
+            let tagged = at_commit.first().map(|tag| match tag.version {
+                VersionInfo::Production(base) => base,
+                _ => unreachable!(),
+            });
+            // An untagged commit is the latest release in its history, never a later release it
+            // doesn't contain
+            let tagged = match tagged {
+                Some(base) => Some(base),
+                None => tags
+                    .reachable_from(repo, commit)?
+                    .into_iter()
+                    .filter_map(|tag| match tag.version {
+                        VersionInfo::Production(base) => Some(base),
+                        _ => None,
+                    })
+                    .max(),
+            };
+            if let (true, None, Some(latest)) = (config.strict, at_commit.first(), tagged) {
+                return Err(GitflowError::Ambiguous {
+                    commit: format_hash(commit, config),
+                    interpretations: vec![
+                        format!("{} from its history", latest),
+                        "an untagged release".to_owned(),
+                    ],
+                });
+            }
+            match tagged {
+                Some(base) => VersionInfo::Production(base),
+                None => {
+                    return Err(GitflowError::NoProductionTag {
+                        branch: branch_name.to_owned(),
+                    })
+                }
+            }
+        }
+        BranchClass::Release(base) | BranchClass::Hotfix(base) => {
+            // A commit that was already released keeps its tagged rc, otherwise this is the next
+            // release candidate after the last one that was tagged
+            let at_commit: Vec<_> = tags
+                .at(commit)
+                .filter(|tag| matches!(tag.version, VersionInfo::Alpha(rc) if rc.base == base))
+                .collect();
+            check_unambiguous(commit, &at_commit, config)?;
+            let tagged = at_commit.first().map(|tag| match tag.version {
+                VersionInfo::Alpha(rc) => rc,
+                _ => unreachable!(),
+            });
+            let rc = match tagged {
+                Some(rc) => rc,
+                None => SemverRC {
+                    base,
+                    rc: tags
+                        .latest_rc(repo, commit, base)?
+                        .map_or(1, |rc| rc.saturating_add(1)),
+                },
+            };
+            VersionInfo::Alpha(rc)
+        }
+        BranchClass::Train(base) => {
+            let shipped = tags
+                .at(commit)
+                .any(|tag| tag.version == VersionInfo::Production(base));
+            if shipped {
+                VersionInfo::Production(base)
+            } else {
+                VersionInfo::Alpha(SemverRC {
+                    base,
+                    rc: train_position(repo, commit, config)?,
+                })
+            }
+        }
+        BranchClass::Develop => VersionInfo::Development,
+        BranchClass::Feature => VersionInfo::Local,
+    })
+}
+
+/// Fails with [`GitflowError::Ambiguous`] in [`GitflowConfig::strict`] mode if `commit` has more
+/// than one of the competing version `tags`
+fn check_unambiguous(
+    commit: git2::Oid,
+    tags: &[&VersionTag],
+    config: &GitflowConfig,
+) -> Result<(), GitflowError> {
+    if config.strict && tags.len() > 1 {
+        return Err(GitflowError::Ambiguous {
+            commit: format_hash(commit, config),
+            interpretations: tags.iter().map(|tag| tag.name.clone()).collect(),
+        });
+    }
+    Ok(())
+}
+
+/// Counts the commits in the history of `commit` that aren't on develop, which is the position of
+/// `commit` in its release train. The first commit is position 1.
+fn train_position(
+    repo: &git2::Repository,
+    commit: git2::Oid,
+    config: &GitflowConfig,
+) -> Result<u8, GitflowError> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(commit)?;
+    if let Some(develop) = graph::develop_tip(repo, config)? {
+        revwalk.hide(develop)?;
+    }
+    let position = revwalk.count().max(1);
+    Ok(position.try_into().unwrap_or(u8::MAX))
+}
+
+/// Compares a release branch version against the latest production release according to
+/// [`GitflowConfig::release_downgrade`]
+fn check_release_downgrade(
+    branch_name: &str,
+    release: SemverBase,
+    tags: &TagIndex,
+    config: &GitflowConfig,
+) -> Result<(), GitflowError> {
+    let latest = match tags.latest_production() {
+        Some(latest) if release <= latest => latest,
+        _ => return Ok(()),
+    };
+    match config.release_downgrade {
+        DowngradeCheck::Ignore => {}
+        DowngradeCheck::Warn => {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "Release branch {} has version {} which is not greater than the latest release {}",
+                branch_name,
+                release,
+                latest
+            );
+        }
+        DowngradeCheck::Error => {
+            return Err(GitflowError::ReleaseDowngrade {
+                branch: branch_name.to_owned(),
+                release,
+                latest,
+            })
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::test_util::{info, TestRepo};
 
     #[test]
     fn parse_release_1() {
@@ -272,4 +1533,1342 @@ mod test {
     fn parse_bad_4() {
         assert!(parse_semver("1.1").is_err());
     }
+
+    #[test]
+    fn parse_build_metadata() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        assert_eq!(
+            parse_semver_with_build("v1.2.3+build.57").unwrap(),
+            (VersionInfo::Production(base), Some(57))
+        );
+        assert_eq!(
+            parse_semver_with_build("v1.2.3-rc.2+linux.build.8").unwrap(),
+            (VersionInfo::Alpha(SemverRC { base, rc: 2 }), Some(8))
+        );
+        assert_eq!(
+            parse_semver_with_build("v1.2.3").unwrap(),
+            (VersionInfo::Production(base), None)
+        );
+        assert_eq!(
+            parse_semver_with_build("v1.2.3+build.abc").unwrap(),
+            (VersionInfo::Production(base), None)
+        );
+        assert_eq!(
+            parse_semver_with_build("v1.2.3+build").unwrap(),
+            (VersionInfo::Production(base), None)
+        );
+        assert_eq!(
+            parse_semver_with_build("v1.2.3+sha.1234").unwrap(),
+            (VersionInfo::Production(base), None)
+        );
+        assert!(parse_semver("v1.2.3+build.57").is_err());
+    }
+
+    #[test]
+    fn version_requirements() {
+        let info = |major, minor, patch| {
+            test_util::info(
+                VersionInfo::Production(SemverBase {
+                    major,
+                    minor,
+                    patch,
+                }),
+                1,
+            )
+        };
+        let req = |req| semver::VersionReq::parse(req).unwrap();
+        assert!(info(1, 4, 2).satisfies(&req("^1.2.0")));
+        assert!(!info(2, 0, 0).satisfies(&req("^1.2.0")));
+        assert!(!info(1, 1, 9).satisfies(&req("^1.2.0")));
+        assert!(info(1, 2, 7).satisfies(&req("~1.2.3")));
+        assert!(!info(1, 3, 0).satisfies(&req("~1.2.3")));
+
+        let mut rc = info(1, 3, 0);
+        rc.version = VersionInfo::Alpha(SemverRC {
+            base: SemverBase {
+                major: 1,
+                minor: 3,
+                patch: 0,
+            },
+            rc: 2,
+        });
+        assert!(!rc.satisfies(&req("^1.2.0")));
+        assert!(rc.satisfies(&req("^1.3.0-rc.1")));
+        assert!(!test_util::info(VersionInfo::Development, 1).satisfies(&req("*")));
+        assert!(!test_util::info(VersionInfo::Local, 1).satisfies(&req("*")));
+    }
+
+    #[test]
+    fn build_number_round_trip() {
+        let info = GitflowInfo::from_version_string("v1.2.3+build.57").unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            })
+        );
+        assert_eq!(info.build_number, 57);
+        assert_eq!(info.to_semver_with_build().unwrap(), "v1.2.3+build.57");
+        assert_eq!(
+            GitflowInfo::from_version_string("v1.2.3")
+                .unwrap()
+                .build_number,
+            0
+        );
+    }
+
+    #[test]
+    fn parse_bad_extra_prerelease() {
+        assert!(parse_semver("v1.2.3-rc.1.2").is_err());
+        assert!(parse_semver("v1.2.3-rc.1.beta").is_err());
+        assert!(parse_semver("v1.2.3-rc").is_err());
+    }
+
+    #[test]
+    fn parse_bad_extreme_numbers() {
+        assert!(parse_semver("v256.0.0").is_err());
+        assert!(parse_semver("v1.2.3-rc.256").is_err());
+        assert!(parse_semver("v18446744073709551616.0.0").is_err());
+        assert!(parse_semver("v1.2.3-rc.99999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn parse_bad_unicode() {
+        assert!(parse_semver("v\u{FF11}.2.3").is_err());
+        assert!(parse_semver("v1.2.3-rc.\u{0663}").is_err());
+        assert!(parse_semver("\u{1F600}").is_err());
+        assert!(parse_semver("v").is_err());
+        assert!(parse_semver("v1.2.3-\u{0301}").is_err());
+    }
+
+    #[test]
+    fn parse_never_panics() {
+        // Cheap stand-in for the fuzz target that runs with the regular test suite
+        let fragments = [
+            "", "v", ".", "-", "+", "rc", "0", "1", "255", "256", "01", "\u{E9}", "\u{0}", "x", " ",
+        ];
+        for a in fragments {
+            for b in fragments {
+                for c in fragments {
+                    for d in fragments {
+                        let _ = parse_semver(&format!("{a}{b}{c}{d}"));
+                        let _ = parse_semver(&format!("v1.2.3-{a}{b}.{c}{d}"));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn version_ordering() {
+        let v1 = SemverBase {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        };
+        let v2 = SemverBase {
+            major: 2,
+            minor: 0,
+            patch: 0,
+        };
+        let rc = |base, rc| VersionInfo::Alpha(SemverRC { base, rc });
+        assert!(VersionInfo::Production(v1) < VersionInfo::Production(v2));
+        assert!(rc(v2, 1) < rc(v2, 2));
+        assert!(rc(v1, 9) < rc(v2, 1));
+        assert!(rc(v2, 1) < VersionInfo::Production(v1));
+        assert!(VersionInfo::Development < rc(v1, 1));
+        assert!(VersionInfo::Local < VersionInfo::Development);
+    }
+
+    #[test]
+    fn valid_transitions() {
+        let base = |minor| SemverBase {
+            major: 1,
+            minor,
+            patch: 0,
+        };
+        let rc = |minor, rc| {
+            info(
+                VersionInfo::Alpha(SemverRC {
+                    base: base(minor),
+                    rc,
+                }),
+                1,
+            )
+        };
+        let prod = |minor| info(VersionInfo::Production(base(minor)), 1);
+        let dev = |build| info(VersionInfo::Development, build);
+        let local = info(VersionInfo::Local, 1);
+
+        assert!(dev(3).is_valid_transition_to(&dev(3)));
+        assert!(dev(3).is_valid_transition_to(&dev(4)));
+        assert!(dev(3).is_valid_transition_to(&rc(1, 1)));
+        assert!(rc(1, 1).is_valid_transition_to(&rc(1, 2)));
+        assert!(rc(1, 2).is_valid_transition_to(&rc(2, 1)));
+        assert!(rc(1, 2).is_valid_transition_to(&prod(1)));
+        assert!(prod(1).is_valid_transition_to(&prod(1)));
+        assert!(prod(1).is_valid_transition_to(&prod(2)));
+        assert!(prod(1).is_valid_transition_to(&rc(2, 1)));
+
+        assert!(!dev(4).is_valid_transition_to(&dev(3)));
+        assert!(!rc(1, 2).is_valid_transition_to(&rc(1, 1)));
+        assert!(!rc(2, 1).is_valid_transition_to(&rc(1, 5)));
+        assert!(!rc(2, 1).is_valid_transition_to(&prod(1)));
+        assert!(!prod(2).is_valid_transition_to(&prod(1)));
+        assert!(!prod(1).is_valid_transition_to(&rc(1, 3)));
+        assert!(!prod(1).is_valid_transition_to(&dev(9)));
+        assert!(!rc(1, 1).is_valid_transition_to(&dev(9)));
+        assert!(!dev(1).is_valid_transition_to(&local));
+        assert!(!local.is_valid_transition_to(&dev(1)));
+    }
+
+    #[test]
+    fn workspace_version_mixed_channels() {
+        let base = |minor| SemverBase {
+            major: 1,
+            minor,
+            patch: 0,
+        };
+        let alpha_1 = VersionInfo::Alpha(SemverRC {
+            base: base(1),
+            rc: 4,
+        });
+        let alpha_2 = VersionInfo::Alpha(SemverRC {
+            base: base(2),
+            rc: 1,
+        });
+        let infos = [
+            info(VersionInfo::Production(base(3)), 10),
+            info(alpha_1, 8),
+            info(alpha_2, 7),
+        ];
+        let workspace = GitflowInfo::workspace_version(&infos).unwrap();
+        assert_eq!(workspace.version, alpha_2);
+
+        let infos = [
+            info(VersionInfo::Production(base(3)), 10),
+            info(VersionInfo::Development, 3),
+            info(VersionInfo::Development, 5),
+        ];
+        let workspace = GitflowInfo::workspace_version(&infos).unwrap();
+        assert_eq!(workspace.version, VersionInfo::Development);
+        assert_eq!(workspace.build_number, 5);
+
+        assert!(GitflowInfo::workspace_version(&[]).is_none());
+    }
+
+    #[test]
+    fn parse_channels() {
+        assert_eq!(
+            "production".parse::<Channel>().unwrap(),
+            Channel::Production
+        );
+        assert_eq!(" Dev\n".parse::<Channel>().unwrap(), Channel::Development);
+        assert!("beta".parse::<Channel>().is_err());
+    }
+
+    #[test]
+    fn channel_override_precedence() {
+        assert_eq!(channel_override(None, None).unwrap(), None);
+        assert_eq!(
+            channel_override(None, Some("alpha")).unwrap(),
+            Some(Channel::Alpha)
+        );
+        assert_eq!(
+            channel_override(Some("local"), Some("alpha")).unwrap(),
+            Some(Channel::Local)
+        );
+        assert!(channel_override(None, Some("beta")).is_err());
+    }
+
+    #[test]
+    fn channel_file_overrides_branch() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0-rc.1");
+        repo.branch("develop");
+        repo.commit("feature");
+        std::fs::write(repo.dir.path().join(CHANNEL_FILE), "alpha\n").unwrap();
+
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0-rc.2");
+
+        std::fs::write(repo.dir.path().join(CHANNEL_FILE), "beta\n").unwrap();
+        assert!(matches!(
+            get_info_from_path(repo.dir.path()),
+            Err(GitflowError::UnknownChannel { .. })
+        ));
+    }
+
+    /// Compares upstream versions like `dpkg --compare-versions`, alternating between non digit
+    /// runs compared with `~` lowest and letters before other characters, and numeric runs
+    fn dpkg_compare(a: &str, b: &str) -> std::cmp::Ordering {
+        fn order(c: Option<u8>) -> i32 {
+            match c {
+                Some(b'~') => -1,
+                None => 0,
+                Some(c) if c.is_ascii_alphabetic() => c as i32,
+                Some(c) => c as i32 + 256,
+            }
+        }
+        let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+        while !a.is_empty() || !b.is_empty() {
+            while a.first().is_some_and(|c| !c.is_ascii_digit())
+                || b.first().is_some_and(|c| !c.is_ascii_digit())
+            {
+                let x = order(a.first().copied().filter(|c| !c.is_ascii_digit()));
+                let y = order(b.first().copied().filter(|c| !c.is_ascii_digit()));
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+            let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+            let (x, y) = (digits(a), digits(b));
+            let number = |s: &[u8]| std::str::from_utf8(s).unwrap().parse::<u64>().unwrap_or(0);
+            match number(&a[..x]).cmp(&number(&b[..y])) {
+                std::cmp::Ordering::Equal => {}
+                ordering => return ordering,
+            }
+            a = &a[x..];
+            b = &b[y..];
+        }
+        std::cmp::Ordering::Equal
+    }
+
+    #[test]
+    fn release_line_labels() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        assert_eq!(VersionInfo::Production(base).release_line_label(), "1.2.x");
+        assert_eq!(
+            VersionInfo::Alpha(SemverRC { base, rc: 4 }).release_line_label(),
+            "1.2.x"
+        );
+        assert_eq!(VersionInfo::Development.release_line_label(), "dev");
+        assert_eq!(VersionInfo::Local.release_line_label(), "local");
+    }
+
+    #[test]
+    fn debian_versions() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        let release = VersionInfo::Production(base).debian_version().unwrap();
+        let rc = |rc| {
+            VersionInfo::Alpha(SemverRC { base, rc })
+                .debian_version()
+                .unwrap()
+        };
+        assert_eq!(release, "1.2.3");
+        assert_eq!(rc(4), "1.2.3~rc4");
+        assert_eq!(VersionInfo::Development.debian_version(), None);
+        assert_eq!(VersionInfo::Local.debian_version(), None);
+
+        use std::cmp::Ordering::Less;
+        assert_eq!(dpkg_compare(&rc(4), &release), Less);
+        assert_eq!(dpkg_compare(&rc(9), &rc(10)), Less);
+        assert_eq!(dpkg_compare("1.2.2", &rc(1)), Less);
+        assert_eq!(dpkg_compare(&release, "1.2.4~rc1"), Less);
+    }
+
+    #[test]
+    fn channel_ranks() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        let mut versions = [
+            VersionInfo::Development,
+            VersionInfo::Production(base),
+            VersionInfo::Local,
+            VersionInfo::Alpha(SemverRC { base, rc: 1 }),
+        ];
+        versions.sort_by_key(VersionInfo::channel_rank);
+        let ranks: Vec<u8> = versions.iter().map(VersionInfo::channel_rank).collect();
+        assert_eq!(ranks, [0, 1, 2, 3]);
+        assert_eq!(
+            versions
+                .iter()
+                .map(VersionInfo::channel)
+                .collect::<Vec<_>>(),
+            [
+                Channel::Local,
+                Channel::Development,
+                Channel::Alpha,
+                Channel::Production
+            ]
+        );
+    }
+
+    #[test]
+    fn packed_u32_round_trip() {
+        let versions = [(0, 0, 0), (1, 2, 3), (255, 255, 255), (3, 0, 17)];
+        for (major, minor, patch) in versions {
+            let base = SemverBase {
+                major,
+                minor,
+                patch,
+            };
+            assert_eq!(SemverBase::from_u32(base.to_u32()).unwrap(), base);
+        }
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        assert_eq!(base.to_u32(), 0x0102_0003);
+        assert!(SemverBase::from_u32(0x0102_0100).is_err());
+    }
+
+    #[test]
+    fn prerelease_variants() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        assert!(!VersionInfo::Production(base).is_prerelease());
+        assert!(VersionInfo::Alpha(SemverRC { base, rc: 1 }).is_prerelease());
+        assert!(!VersionInfo::Development.is_prerelease());
+        assert!(!VersionInfo::Local.is_prerelease());
+    }
+
+    #[test]
+    fn dev_version_format() {
+        let next = SemverBase {
+            major: 1,
+            minor: 3,
+            patch: 0,
+        };
+        assert_eq!(
+            VersionInfo::Development.to_dev_version(next, 42),
+            "v1.3.0-dev.42"
+        );
+        assert_eq!(VersionInfo::Local.to_dev_version(next, 7), "v1.3.0-local.7");
+        let released = VersionInfo::Production(SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 1,
+        });
+        assert_eq!(released.to_dev_version(next, 42), "v1.2.1");
+    }
+
+    #[test]
+    fn dev_version_on_develop() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.1.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.version, VersionInfo::Development);
+        assert_eq!(info.dev_version.as_deref(), Some("v1.2.0-dev.2"));
+
+        // Once a release is branched, develop works towards the release after it
+        repo.branch("release/v1.2.0");
+        repo.checkout("develop");
+        repo.commit("next feature");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.dev_version.as_deref(), Some("v1.3.0-dev.3"));
+    }
+
+    #[test]
+    fn merged_branch_on_develop() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.branch("feature/login");
+        repo.commit("login page");
+        repo.checkout("develop");
+        repo.merge("feature/login", "Merge branch 'feature/login' into develop");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.merged_branch.as_deref(), Some("feature/login"));
+        assert_eq!(info.build_number, 3);
+
+        repo.commit("direct commit");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.version, VersionInfo::Development);
+        assert_eq!(info.merged_branch, None);
+
+        repo.commit("Add a logout button (#13)");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.merged_branch, None);
+        assert_eq!(info.merged_pull_request.as_deref(), Some("#13"));
+        assert_eq!(info.build_number, 5);
+    }
+
+    #[test]
+    fn count_integrations() {
+        let config = GitflowConfig {
+            count_integrations: true,
+            ..Default::default()
+        };
+
+        // Merge commits: the two commits of the branch only count once
+        let merged = TestRepo::new();
+        merged.commit("initial");
+        merged.branch("develop");
+        merged.branch("feature/login");
+        merged.commit("login page");
+        merged.commit("login tests");
+        merged.checkout("develop");
+        merged.merge("feature/login", "Merge branch 'feature/login' into develop");
+        let info = get_info_from_path(merged.dir.path()).unwrap();
+        assert_eq!(info.build_number, 4);
+        let info = get_info_with_config(merged.dir.path(), &config).unwrap();
+        assert_eq!(info.build_number, 2);
+
+        // Squash merges already count one per integration
+        let squashed = TestRepo::new();
+        squashed.commit("initial");
+        squashed.branch("develop");
+        squashed.commit("Add a login page (#12)");
+        let info = get_info_from_path(squashed.dir.path()).unwrap();
+        assert_eq!(info.build_number, 2);
+        let info = get_info_with_config(squashed.dir.path(), &config).unwrap();
+        assert_eq!(info.build_number, 2);
+    }
+
+    #[test]
+    fn calendar_dev_version() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        // 2024-05-14 23:59:00 UTC
+        repo.commit_at("feature", 1_715_731_140);
+        let config = GitflowConfig {
+            dev_version_scheme: DevVersionScheme::Calendar,
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.dev_version.as_deref(), Some("2024.05.14-dev.2"));
+    }
+
+    #[test]
+    fn dev_version_without_releases() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.dev_version, None);
+    }
+
+    #[test]
+    fn submodule_pins_from_index() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        let pin = git2::Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+        repo.commit_submodule("lib", pin);
+        repo.tag("v1.0.0");
+
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert!(info.submodule_pins.is_empty());
+
+        let config = GitflowConfig {
+            submodule_pins: true,
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(
+            info.submodule_pins,
+            vec![("lib".to_owned(), pin.to_string())]
+        );
+    }
+
+    #[test]
+    fn codenames() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v22.4.0");
+        let base = |major| SemverBase {
+            major,
+            minor: 0,
+            patch: 0,
+        };
+        let mut config = GitflowConfig {
+            codenames: vec![
+                Codename {
+                    start: base(20),
+                    end: base(22),
+                    name: "Focal".to_owned(),
+                },
+                Codename {
+                    start: base(22),
+                    end: base(24),
+                    name: "Jammy".to_owned(),
+                },
+            ],
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.codename.as_deref(), Some("Jammy"));
+
+        config.codenames.remove(1);
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.codename, None);
+
+        repo.branch("develop");
+        repo.commit("feature");
+        config.codenames[0].end = base(30);
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.codename, None);
+    }
+
+    #[test]
+    fn abbreviated_hashes() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        let pin = git2::Oid::from_str("1111111111111111111111111111111111111111").unwrap();
+        let head = repo.commit_submodule("lib", pin);
+        repo.tag("v1.0.0");
+        let head = repo.repo.find_commit(head).unwrap();
+
+        let config = GitflowConfig {
+            submodule_pins: true,
+            hash_length: Some(12),
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.commit_hash, head.id().to_string()[..12]);
+        assert_eq!(info.tree_hash, head.tree_id().to_string()[..12]);
+        assert_eq!(
+            info.submodule_pins,
+            vec![("lib".to_owned(), "1".repeat(12))]
+        );
+
+        let (_, to) = changelog_range(&repo.repo, &config).unwrap();
+        assert_eq!(to, info.commit_hash);
+        let short = GitflowConfig {
+            hash_length: Some(1),
+            ..config.clone()
+        };
+        let short = get_info_with_config(repo.dir.path(), &short).unwrap();
+        assert_eq!(short.commit_hash.len(), 4);
+        assert_eq!(short.tree_hash.len(), 4);
+
+        // Human readable text shortens hashes to 7 characters unless they are already shorter
+        let seven = &info.commit_hash[..7];
+        let clock = FixedClock(info.commit_time + 30 * 86_400);
+        let day = std::time::Duration::from_secs(86_400);
+        for (info, hash) in [(&info, seven), (&short, short.commit_hash.as_str())] {
+            assert_eq!(info.stamp().split(' ').nth(2), Some(hash));
+            #[cfg(feature = "color")]
+            assert!(info.colored_banner().contains(&format!(" {}, ", hash)));
+            let warning = info.stale_warning(day, &clock).unwrap();
+            assert!(warning.contains(&format!(" {} ", hash)));
+        }
+        let explanation = explain(&repo.repo, &config).unwrap();
+        assert_eq!(
+            explanation.steps[0],
+            format!("HEAD is commit {}", info.commit_hash)
+        );
+        let violations = validate_gitflow(&repo.repo, &config).unwrap();
+        assert!(violations[0]
+            .description
+            .starts_with(&format!("Commit {} ", info.commit_hash)));
+
+        // Errors about commits off any branch abbreviate the same way
+        repo.repo.set_head_detached(head.id()).unwrap();
+        match validate_proposed_tag(&repo.repo, "v1.0.1", &config) {
+            Err(GitflowError::NoBranch { commit }) => assert_eq!(commit, info.commit_hash),
+            other => panic!("expected no branch, got {:?}", other),
+        }
+        let detached = repo.commit("detached");
+        match channel_transition(&repo.repo, head.id(), detached, &config) {
+            Err(GitflowError::NoBranch { commit }) => {
+                assert_eq!(commit, detached.to_string()[..12])
+            }
+            other => panic!("expected no branch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pre_tag_commits() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let hotfix = repo.commit("hotfix");
+        repo.commit("release");
+        repo.tag("v1.1.0");
+        let config = GitflowConfig::default();
+        let info = get_info_for_commit(&repo.repo, hotfix, "main", &config).unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 0,
+                patch: 0
+            })
+        );
+
+        repo.branch("develop");
+        repo.branch("release/v1.2.0");
+        let first = repo.commit("first candidate");
+        repo.commit("second candidate");
+        repo.tag("v1.2.0-rc.1");
+        repo.commit("third candidate");
+        repo.tag("v1.2.0-rc.2");
+        let info = get_info_for_commit(&repo.repo, first, "release/v1.2.0", &config).unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Alpha(SemverRC {
+                base: SemverBase {
+                    major: 1,
+                    minor: 2,
+                    patch: 0
+                },
+                rc: 1
+            })
+        );
+    }
+
+    #[test]
+    fn submodule_checkout() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+
+        let workdir = repo.dir.path().join("lib");
+        let submodule = git2::Repository::init_opts(
+            repo.dir.path().join(".git/modules/lib"),
+            git2::RepositoryInitOptions::new()
+                .workdir_path(&workdir)
+                .initial_head("main"),
+        )
+        .unwrap();
+        assert!(workdir.join(".git").is_file());
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        let tree = submodule.treebuilder(None).unwrap().write().unwrap();
+        let tree = submodule.find_tree(tree).unwrap();
+        let pin = submodule
+            .commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+        let pin_commit = submodule.find_object(pin, None).unwrap();
+        submodule
+            .tag_lightweight("v2.0.0", &pin_commit, false)
+            .unwrap();
+        repo.commit_submodule("lib", pin);
+        repo.tag("v1.1.0");
+
+        let info = get_info_from_path(&workdir).unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Production(SemverBase {
+                major: 2,
+                minor: 0,
+                patch: 0
+            })
+        );
+
+        let config = GitflowConfig {
+            submodule_context: SubmoduleContext::Superproject,
+            ..Default::default()
+        };
+        let info = get_info_with_config(&workdir, &config).unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 1,
+                patch: 0
+            })
+        );
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(
+            info.version,
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 1,
+                patch: 0
+            })
+        );
+    }
+
+    #[test]
+    fn tree_hash_ignores_commit_message() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        let first = get_info_from_path(repo.dir.path()).unwrap();
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        head.amend(Some("HEAD"), None, None, None, Some("reworded"), None)
+            .unwrap();
+        let amended = get_info_from_path(repo.dir.path()).unwrap();
+
+        assert_ne!(first.commit_hash, amended.commit_hash);
+        assert_eq!(first.tree_hash, amended.tree_hash);
+        assert_eq!(first.tree_hash, head.tree_id().to_string());
+        assert_eq!(first.commit_time, head.time().seconds());
+    }
+
+    #[test]
+    fn release_train_versions() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("train/2024.05");
+        let config = GitflowConfig {
+            release_train_prefix: Some("train/".to_owned()),
+            ..Default::default()
+        };
+        let version = |repo: &TestRepo| {
+            get_info_with_config(repo.dir.path(), &config)
+                .unwrap()
+                .version
+                .get_semver()
+                .unwrap()
+        };
+
+        // The train was cut but has no commits of its own yet
+        assert_eq!(version(&repo), "v24.5.0-rc.1");
+        repo.commit("stabilize");
+        repo.commit("stabilize more");
+        assert_eq!(version(&repo), "v24.5.0-rc.2");
+        repo.tag("train/2024.05");
+        assert_eq!(version(&repo), "v24.5.0");
+    }
+
+    #[test]
+    fn release_candidate_scoped_to_head() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.branch("release/v1.2.3");
+        repo.commit("fix");
+        repo.tag("v1.2.3-rc.1");
+        let earlier = repo.commit("another fix");
+        repo.commit("final fix");
+        repo.tag("v1.2.3-rc.2");
+        repo.commit("after rc.2");
+        let version = |repo: &TestRepo| {
+            get_info_from_path(repo.dir.path())
+                .unwrap()
+                .version
+                .get_semver()
+                .unwrap()
+        };
+        assert_eq!(version(&repo), "v1.2.3-rc.3");
+
+        // Moving the branch back to before rc.2 was tagged ignores the later tag
+        repo.repo
+            .reference("refs/heads/release/v1.2.3", earlier, true, "reset")
+            .unwrap();
+        assert_eq!(version(&repo), "v1.2.3-rc.2");
+    }
+
+    #[test]
+    fn fallback_version() {
+        let config = GitflowConfig {
+            fallback_version: Some(VersionInfo::Local),
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        assert!(get_info_from_path(dir.path()).is_err());
+        let info = get_info_with_config(dir.path(), &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Local);
+        assert_eq!(info.commit_hash, "");
+
+        // A production branch without tags is an error that the fallback also masks
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        assert!(get_info_from_path(repo.dir.path()).is_err());
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Local);
+
+        // Versions that can be determined are unaffected
+        repo.tag("v1.0.0");
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+    }
+
+    #[test]
+    fn info_for_ref_in_bare_repo() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.1.0");
+        let fix = repo.commit("fix");
+        repo.tag("v1.1.0-rc.1");
+        repo.checkout("develop");
+
+        let dir = tempfile::tempdir().unwrap();
+        let bare = git2::build::RepoBuilder::new()
+            .bare(true)
+            .clone(repo.dir.path().to_str().unwrap(), dir.path())
+            .unwrap();
+        assert!(bare.is_bare());
+        // The clone only has a local branch for the source's HEAD, develop
+        for branch in ["main", "release/v1.1.0"] {
+            let tip = bare
+                .refname_to_id(&format!("refs/remotes/origin/{}", branch))
+                .unwrap();
+            bare.branch(branch, &bare.find_commit(tip).unwrap(), false)
+                .unwrap();
+        }
+        let config = GitflowConfig::default();
+
+        let info = get_info_for_ref(&bare, "refs/heads/release/v1.1.0", &config).unwrap();
+        assert_eq!(info.branch_name, "release/v1.1.0");
+        assert_eq!(info.version.get_semver().unwrap(), "v1.1.0-rc.1");
+        assert_eq!(info.commit_hash, fix.to_string());
+        let info = get_info_for_ref(&bare, "main", &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+        let info = get_info_for_ref(&bare, "develop", &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Development);
+        assert!(matches!(
+            get_info_for_ref(&bare, "refs/tags/v1.0.0", &config),
+            Err(GitflowError::NoBranch { .. })
+        ));
+
+        // A pushed commit that isn't on the branch yet
+        let info = get_info_for_commit(&bare, fix, "release/v1.1.0", &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.1.0-rc.1");
+    }
+
+    #[test]
+    fn symbolic_deployment_refs() {
+        let repo = TestRepo::new();
+        let release = repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("fix");
+        repo.branch("develop");
+        repo.commit("feature");
+        let refs = &repo.repo;
+        refs.reference_symbolic("refs/deploy/production", "refs/tags/v1.0.0", false, "")
+            .unwrap();
+        refs.reference_symbolic("refs/deploy/live", "refs/deploy/production", false, "")
+            .unwrap();
+        refs.reference_symbolic("refs/deploy/staging", "refs/heads/develop", false, "")
+            .unwrap();
+        let config = GitflowConfig::default();
+
+        let info = get_info_for_symbolic_ref(refs, "refs/deploy/live", &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+        assert_eq!(info.commit_hash, release.to_string());
+        assert_eq!(info.branch_name, "");
+        let info = get_info_for_symbolic_ref(refs, "deploy/staging", &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Development);
+        assert_eq!(info.branch_name, "develop");
+
+        refs.reference("refs/deploy/broken", repo.commit("untagged"), false, "")
+            .unwrap();
+        assert!(matches!(
+            get_info_for_symbolic_ref(refs, "refs/deploy/broken", &config),
+            Err(GitflowError::NoBranch { .. })
+        ));
+    }
+
+    #[test]
+    fn strict_mode() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.tag("v1.1.0");
+        let mut config = GitflowConfig::default();
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert!(info.version.is_production());
+
+        config.strict = true;
+        match get_info_with_config(repo.dir.path(), &config) {
+            Err(GitflowError::Ambiguous {
+                interpretations, ..
+            }) => assert_eq!(interpretations, vec!["v1.0.0", "v1.1.0"]),
+            other => panic!("expected ambiguity, got {:?}", other),
+        }
+
+        repo.commit("untagged");
+        config.strict = false;
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.1.0");
+        config.strict = true;
+        assert!(matches!(
+            get_info_with_config(repo.dir.path(), &config),
+            Err(GitflowError::Ambiguous { .. })
+        ));
+
+        repo.branch("release/v1.2.0");
+        repo.tag("v1.2.0-rc.1");
+        repo.tag("v1.2.0-rc.2");
+        assert!(matches!(
+            get_info_with_config(repo.dir.path(), &config),
+            Err(GitflowError::Ambiguous { .. })
+        ));
+
+        let dir = tempfile::tempdir().unwrap();
+        config.fallback_version = Some(VersionInfo::Local);
+        assert!(get_info_with_config(dir.path(), &config).is_err());
+        config.strict = false;
+        assert!(get_info_with_config(dir.path(), &config).is_ok());
+    }
+
+    #[test]
+    fn shared_object_store() {
+        let store = TestRepo::new();
+        store.commit("initial");
+        store.tag_annotated("v1.0.0");
+        store.branch("develop");
+        let head = store.commit("feature");
+
+        // A repository with only refs of its own, reading every object from the store
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        let objects = store.repo.path().join("objects");
+        std::fs::write(
+            repo.path().join("objects/info/alternates"),
+            format!("{}\n", objects.display()),
+        )
+        .unwrap();
+        let repo = git2::Repository::open(dir.path()).unwrap();
+        for reference in store.repo.references().unwrap() {
+            let reference = reference.unwrap();
+            let (name, target) = (reference.name().unwrap(), reference.target().unwrap());
+            repo.reference(name, target, true, "").unwrap();
+        }
+        repo.set_head("refs/heads/develop").unwrap();
+        let local_objects = std::fs::read_dir(repo.path().join("objects")).unwrap();
+        for entry in local_objects {
+            assert!(["info", "pack"].contains(&entry.unwrap().file_name().to_str().unwrap()));
+        }
+
+        let config = GitflowConfig {
+            require_annotated_tags: true,
+            ..Default::default()
+        };
+        let info = get_info_with_config(dir.path(), &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Development);
+        assert_eq!(info.commit_hash, head.to_string());
+        assert_eq!(info.build_number, 2);
+        assert_eq!(info.dev_version.as_deref(), Some("v1.1.0-dev.2"));
+        let info = get_info_for_ref(&repo, "main", &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+    }
+
+    #[test]
+    fn detached_on_rc_tag() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.branch("release/v1.1.0");
+        let rc = repo.commit("fix");
+        repo.tag("v1.1.0-rc.1");
+        repo.commit("another fix");
+        repo.tag("v1.1.0-rc.2");
+        repo.checkout("develop");
+        repo.repo
+            .find_branch("release/v1.1.0", git2::BranchType::Local)
+            .unwrap()
+            .delete()
+            .unwrap();
+
+        repo.repo.set_head_detached(rc).unwrap();
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.1.0-rc.1");
+        assert_eq!(info.branch_name, "");
+        assert_eq!(info.commit_hash, rc.to_string());
+
+        let untagged = repo.repo.find_commit(rc).unwrap().parent_id(0).unwrap();
+        repo.repo.set_head_detached(untagged).unwrap();
+        assert!(matches!(
+            get_info_from_path(repo.dir.path()),
+            Err(GitflowError::NoBranch { .. })
+        ));
+    }
+
+    #[test]
+    fn resolution_timeout() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+
+        // A filter slow enough that resolution can't finish in time
+        let mut config = GitflowConfig {
+            timeout: Some(std::time::Duration::from_millis(1)),
+            tag_filter: Some(TagFilter::new(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                true
+            })),
+            ..Default::default()
+        };
+        assert!(matches!(
+            get_info_with_config(repo.dir.path(), &config),
+            Err(GitflowError::Timeout { .. })
+        ));
+
+        config.fallback_version = Some(VersionInfo::Local);
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version, VersionInfo::Local);
+
+        config.timeout = Some(std::time::Duration::from_secs(60));
+        config.tag_filter = None;
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.0.0");
+    }
+
+    #[test]
+    fn timed_out_builds_not_counted() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let counter = repo.dir.path().join(".gitflow-build-counter");
+        let mut config = GitflowConfig {
+            timeout: Some(std::time::Duration::from_millis(1)),
+            tag_filter: Some(TagFilter::new(|_| {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                true
+            })),
+            build_counter_file: Some(counter.clone()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            get_info_with_config(repo.dir.path(), &config),
+            Err(GitflowError::Timeout { .. })
+        ));
+        let gitflow = Gitflow::open_with_config(repo.dir.path(), config.clone()).unwrap();
+        assert!(matches!(gitflow.info(), Err(GitflowError::Timeout { .. })));
+
+        // Once the abandoned workers are done, they still haven't touched the counter
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        assert!(!counter.exists());
+        config.timeout = None;
+        config.tag_filter = None;
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.build_counter, Some(1));
+    }
+
+    #[test]
+    fn author_and_committer() {
+        let repo = TestRepo::new();
+        repo.commit_as("initial", "Alice", "alice@example.com");
+        repo.tag("v1.0.0");
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.author, "Alice <alice@example.com>");
+        assert_eq!(info.committer, "Alice <alice@example.com>");
+
+        let head = repo.repo.head().unwrap().peel_to_commit().unwrap();
+        let author = git2::Signature::now("Alice", "alice@example.com").unwrap();
+        let committer = git2::Signature::now("Bob", "bob@example.com").unwrap();
+        repo.repo
+            .commit(
+                Some("HEAD"),
+                &author,
+                &committer,
+                "rebased",
+                &head.tree().unwrap(),
+                &[&head],
+            )
+            .unwrap();
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.author, "Alice <alice@example.com>");
+        assert_eq!(info.committer, "Bob <bob@example.com>");
+    }
+
+    #[test]
+    fn branch_overrides() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v2.0.0");
+        repo.tag("v2.1.0-rc.3");
+        repo.branch("legacy-main");
+        repo.commit("backport");
+        let base = SemverBase {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        };
+        let mut config = GitflowConfig::default();
+        config.branch_overrides.insert(
+            "legacy-main".to_owned(),
+            ChannelOverride {
+                channel: Channel::Production,
+                base: Some(base),
+            },
+        );
+        let version = |repo: &TestRepo, config: &GitflowConfig| {
+            get_info_with_config(repo.dir.path(), config)
+                .unwrap()
+                .version
+        };
+        assert_eq!(version(&repo, &config), VersionInfo::Production(base));
+
+        // Overrides win over the branch patterns
+        repo.branch("v3.0.0");
+        config.branch_overrides.insert(
+            "v3.0.0".to_owned(),
+            ChannelOverride {
+                channel: Channel::Development,
+                base: None,
+            },
+        );
+        assert_eq!(version(&repo, &config), VersionInfo::Development);
+
+        repo.branch("qa");
+        config.branch_overrides.insert(
+            "qa".to_owned(),
+            ChannelOverride {
+                channel: Channel::Alpha,
+                base: None,
+            },
+        );
+        assert_eq!(version(&repo, &config).get_semver().unwrap(), "v2.1.0-rc.4");
+        config.branch_overrides.insert(
+            "qa".to_owned(),
+            ChannelOverride {
+                channel: Channel::Alpha,
+                base: Some(base),
+            },
+        );
+        assert_eq!(version(&repo, &config).get_semver().unwrap(), "v1.0.0-rc.1");
+    }
+
+    #[test]
+    fn build_counter_increments() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        let mut config = GitflowConfig::default();
+        assert_eq!(
+            get_info_with_config(repo.dir.path(), &config)
+                .unwrap()
+                .build_counter,
+            None
+        );
+
+        config.build_counter_file = Some("target/.gitflow-build-counter".into());
+        let counter = |config: &GitflowConfig| {
+            get_info_with_config(repo.dir.path(), config)
+                .unwrap()
+                .build_counter
+        };
+        assert_eq!(counter(&config), Some(1));
+        assert_eq!(counter(&config), Some(2));
+        config.dry_run = true;
+        assert_eq!(counter(&config), Some(3));
+        config.dry_run = false;
+        assert_eq!(counter(&config), Some(3));
+        assert_eq!(
+            std::fs::read_to_string(repo.dir.path().join("target/.gitflow-build-counter")).unwrap(),
+            "3\n"
+        );
+    }
+
+    #[test]
+    fn tree_file_count() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        assert_eq!(
+            get_info_from_path(repo.dir.path()).unwrap().tree_file_count,
+            0
+        );
+
+        // file, VERSION, docs/guide.md, docs/api/index.md, link -> file, and a submodule
+        repo.commit_file("VERSION", "1.0.0", "add version");
+        let git = &repo.repo;
+        let blob = git.blob(b"docs").unwrap();
+        let mut api = git.treebuilder(None).unwrap();
+        api.insert("index.md", blob, 0o100644).unwrap();
+        let mut docs = git.treebuilder(None).unwrap();
+        docs.insert("guide.md", blob, 0o100644).unwrap();
+        docs.insert("api", api.write().unwrap(), 0o040000).unwrap();
+        let head = git.head().unwrap().peel_to_commit().unwrap();
+        let mut root = git.treebuilder(Some(&head.tree().unwrap())).unwrap();
+        root.insert("docs", docs.write().unwrap(), 0o040000)
+            .unwrap();
+        root.insert("link", git.blob(b"file").unwrap(), 0o120000)
+            .unwrap();
+        root.insert("vendor", head.id(), 0o160000).unwrap();
+        let tree = git.find_tree(root.write().unwrap()).unwrap();
+        let sig = head.author();
+        git.commit(Some("HEAD"), &sig, &sig, "docs", &tree, &[&head])
+            .unwrap();
+        repo.tag("v1.0.1");
+
+        let config = GitflowConfig {
+            tree_file_count: true,
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.tree_file_count, 6);
+    }
+
+    fn release_downgrade_repo() -> TestRepo {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.3.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.2.0");
+        repo
+    }
+
+    #[test]
+    fn release_downgrade_ignored_by_default() {
+        let repo = release_downgrade_repo();
+        let info = get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(info.branch_name, "release/v1.2.0");
+        assert!(info.version.is_alpha());
+    }
+
+    #[test]
+    fn release_downgrade_warn() {
+        let repo = release_downgrade_repo();
+        let config = GitflowConfig {
+            release_downgrade: DowngradeCheck::Warn,
+            ..Default::default()
+        };
+        assert!(get_info_with_config(repo.dir.path(), &config).is_ok());
+    }
+
+    #[test]
+    fn release_downgrade_error() {
+        let repo = release_downgrade_repo();
+        let config = GitflowConfig {
+            release_downgrade: DowngradeCheck::Error,
+            ..Default::default()
+        };
+        let err = get_info_with_config(repo.dir.path(), &config).unwrap_err();
+        assert!(matches!(
+            err,
+            GitflowError::ReleaseDowngrade {
+                latest: SemverBase {
+                    major: 1,
+                    minor: 3,
+                    patch: 0
+                },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn release_upgrade_allowed() {
+        let repo = release_downgrade_repo();
+        repo.checkout("develop");
+        repo.branch("release/v1.4.0");
+        let config = GitflowConfig {
+            release_downgrade: DowngradeCheck::Error,
+            ..Default::default()
+        };
+        let info = get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.4.0-rc.1");
+    }
 }
@@ -0,0 +1,427 @@
+//! Inspection of how branches were merged
+
+use crate::{
+    classify_branch, dev_version, get_info_for_commit, graph, local_branches, BranchClass,
+    GitflowConfig, GitflowError, GitflowInfo, SemverBase, TagIndex, VersionInfo,
+};
+use git2::Repository;
+
+/// How feature branches are integrated into develop
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum IntegrationStyle {
+    /// Branches are merged with a merge commit, keeping their commits in develop's history. Build
+    /// numbers grow by the number of commits on the branch plus one for each integration, or just
+    /// one with [`GitflowConfig::count_integrations`].
+    MergeCommit,
+
+    /// Branches are squashed into a single commit referencing the pull request, e.g.
+    /// `Add a login page (#12)`. Build numbers grow by one for each integration, and the pull
+    /// request is recorded in [`GitflowInfo::merged_pull_request`], as the branch name isn't.
+    SquashMerge,
+}
+
+/// Number of commits on develop's first parent history inspected by [`integration_style`]
+const INTEGRATION_SCAN_LIMIT: usize = 100;
+
+/// Detects whether develop integrates branches with merge commits or squash merges, from the most
+/// recent commits on its first parent history. Whichever style is more common wins, with ties
+/// going to merge commits. Returns `None` if there is no develop branch or no integrations were
+/// found.
+pub fn integration_style(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Option<IntegrationStyle>, GitflowError> {
+    let tip = match graph::develop_tip(repo, config)? {
+        Some(tip) => tip,
+        None => return Ok(None),
+    };
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(tip)?;
+    revwalk.simplify_first_parent()?;
+    let (mut merges, mut squashes) = (0, 0);
+    for oid in revwalk.take(INTEGRATION_SCAN_LIMIT) {
+        let commit = repo.find_commit(oid?)?;
+        if commit.parent_count() > 1 {
+            merges += 1;
+        } else if commit.message().and_then(squash_pr_reference).is_some() {
+            squashes += 1;
+        }
+    }
+    Ok(match (merges, squashes) {
+        (0, 0) => None,
+        (merges, squashes) if squashes > merges => Some(IntegrationStyle::SquashMerge),
+        _ => Some(IntegrationStyle::MergeCommit),
+    })
+}
+
+/// Determines the version develop would have after merging the current branch into it, without
+/// merging, so pull requests can show "merging this makes develop X". The merge is simulated as
+/// described in [`project_develop_merge_info`].
+///
+/// The version of a development build doesn't include its build number, so the projected build
+/// number and [`GitflowInfo::dev_version`] are only on the info of [`project_develop_merge_info`].
+pub fn project_develop_merge(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<VersionInfo, GitflowError> {
+    Ok(project_develop_merge_info(repo, config)?.version)
+}
+
+/// Previews the develop build that merging the current branch into develop would produce, like
+/// [`project_develop_merge`] but giving the whole info of the build.
+///
+/// The merge is assumed to be a merge commit (`git merge --no-ff`) made on the current tip of
+/// develop, adding the merge commit on top of the combined history of both branches to the build
+/// number, or on top of develop's first parent history with
+/// [`GitflowConfig::count_integrations`]. The next release develop works towards is kept, so
+/// [`GitflowInfo::dev_version`] reflects the new build number. As the merge commit doesn't exist,
+/// the commit and tree hashes are empty, and a calendar [`GitflowConfig::dev_version_scheme`]
+/// dates it by the newer of the two branch tips. If develop already contains HEAD, nothing would
+/// be merged and the info of develop's tip is returned instead.
+///
+/// Fails with [`GitflowError::NoBranchPoint`] if there is no develop branch or HEAD shares no
+/// history with it, as the branches couldn't be merged.
+pub fn project_develop_merge_info(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<GitflowInfo, GitflowError> {
+    let head = repo.head()?;
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+    let head = head.peel_to_commit()?.id();
+    let develop = match graph::develop_tip(repo, config)? {
+        Some(develop) => develop,
+        None => return Err(GitflowError::NoBranchPoint { branch }),
+    };
+    let mut info = get_info_for_commit(repo, develop, "develop", config)?;
+    if head == develop || repo.graph_descendant_of(develop, head)? {
+        return Ok(info);
+    }
+    if graph::is_orphan(repo, head, config)? {
+        return Err(GitflowError::NoBranchPoint { branch });
+    }
+
+    let integrations_only = config.count_integrations
+        && integration_style(repo, config)? == Some(IntegrationStyle::MergeCommit);
+    let history = if integrations_only {
+        graph::count_first_parent_history(repo, develop)?
+    } else {
+        let mut revwalk = repo.revwalk()?;
+        revwalk.push(develop)?;
+        revwalk.push(head)?;
+        revwalk.count()
+    };
+    info.build_number = history as u64 + 1;
+    let tags = TagIndex::new(repo, config)?;
+    let time = repo
+        .find_commit(develop)?
+        .time()
+        .seconds()
+        .max(repo.find_commit(head)?.time().seconds());
+    info.dev_version = dev_version(repo, &tags, info.version, info.build_number, time, config)?;
+    info.commit_hash = String::new();
+    info.tree_hash = String::new();
+    info.merged_branch = Some(branch);
+    info.merged_pull_request = None;
+    Ok(info)
+}
+
+/// Determines the production version the HEAD commit will have once it is tagged, for release
+/// automation that runs before the tag exists, such as on the merge of a release branch into the
+/// production branch. A commit that already has a production tag gives the tagged version.
+/// Otherwise the version comes from the release or hotfix branch HEAD merges, named by its merge
+/// commit message or, failing that, a local branch at its second parent.
+///
+/// Fails with [`GitflowError::NotReleaseBranch`] if HEAD isn't tagged and doesn't merge a release
+/// or hotfix branch.
+pub fn projected_production(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<SemverBase, GitflowError> {
+    let head = repo.head()?;
+    let commit = head.peel_to_commit()?;
+    let tags = TagIndex::new(repo, config)?;
+    let tagged = tags.at(commit.id()).find_map(|tag| match tag.version {
+        VersionInfo::Production(base) => Some(base),
+        _ => None,
+    });
+    if let Some(base) = tagged {
+        return Ok(base);
+    }
+
+    let mut merged: Vec<String> = commit
+        .message()
+        .and_then(merged_branch_name)
+        .into_iter()
+        .collect();
+    if let Some(parent) = commit.parent_ids().nth(1) {
+        for (name, tip) in local_branches(repo)? {
+            if tip == parent {
+                merged.push(name);
+            }
+        }
+    }
+    for name in &merged {
+        if let BranchClass::Release(base) | BranchClass::Hotfix(base) =
+            classify_branch(name, config)
+        {
+            return Ok(base);
+        }
+    }
+    Err(GitflowError::NotReleaseBranch {
+        branch: match merged.into_iter().next() {
+            Some(name) => name,
+            None => head.shorthand().unwrap_or_default().to_owned(),
+        },
+    })
+}
+
+/// Extracts the pull request a squash merge came from, out of the commit message written by
+/// GitHub or GitLab:
+/// - `Add a login page (#12)` gives `#12`
+/// - A `See merge request group/project!12` trailer gives `!12`
+pub(crate) fn squash_pr_reference(message: &str) -> Option<String> {
+    let subject = message.lines().next()?.trim_end();
+    if let Some(rest) = subject.strip_suffix(')') {
+        if let Some((_, number)) = rest.rsplit_once("(#") {
+            if !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()) {
+                return Some(format!("#{}", number));
+            }
+        }
+    }
+    message.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("See merge request ")?;
+        let (_, number) = rest.rsplit_once('!')?;
+        Some(format!("!{}", number))
+            .filter(|_| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+/// Extracts the name of the branch that was merged from the message of a merge commit, as written
+/// by `git merge`, `git pull`, and GitHub pull requests:
+/// - `Merge branch 'feature/login' into develop`
+/// - `Merge remote-tracking branch 'origin/feature/login'`
+/// - `Merge pull request #12 from user/feature/login`
+pub(crate) fn merged_branch_name(message: &str) -> Option<String> {
+    let subject = message.lines().next()?.trim();
+    if let Some(rest) = subject.strip_prefix("Merge pull request #") {
+        // The source is `<owner>/<branch>`, and the branch may itself contain slashes
+        let (_, source) = rest.split_once(" from ")?;
+        let source = source.split_whitespace().next()?;
+        let (_, branch) = source.split_once('/')?;
+        return Some(branch.to_owned()).filter(|branch| !branch.is_empty());
+    }
+
+    let (remote, rest) = if let Some(rest) = subject.strip_prefix("Merge remote-tracking branch '")
+    {
+        (true, rest)
+    } else {
+        (false, subject.strip_prefix("Merge branch '")?)
+    };
+    let (branch, _) = rest.split_once('\'')?;
+    let branch = if remote {
+        branch.split_once('/').map_or(branch, |(_, branch)| branch)
+    } else {
+        branch
+    };
+    Some(branch.to_owned()).filter(|branch| !branch.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+
+    #[test]
+    fn merge_messages() {
+        let cases = [
+            (
+                "Merge branch 'feature/login' into develop",
+                Some("feature/login"),
+            ),
+            ("Merge branch 'login'", Some("login")),
+            (
+                "Merge branch 'login' of https://example.com/repo into develop",
+                Some("login"),
+            ),
+            (
+                "Merge remote-tracking branch 'origin/feature/login'",
+                Some("feature/login"),
+            ),
+            (
+                "Merge pull request #12 from user/feature/login\n\nAdd a login page",
+                Some("feature/login"),
+            ),
+            ("Add a login page", None),
+            ("Merge branch ''", None),
+        ];
+        for (message, branch) in cases {
+            assert_eq!(
+                merged_branch_name(message).as_deref(),
+                branch,
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn squash_messages() {
+        let cases = [
+            ("Add a login page (#12)", Some("#12")),
+            (
+                "Add a login page (#12)\n\n* login page\n* tests",
+                Some("#12"),
+            ),
+            (
+                "Add a login page\n\nSee merge request group/project!34",
+                Some("!34"),
+            ),
+            ("Add a login page", None),
+            ("Fix (#abc)", None),
+            ("Fix ()", None),
+        ];
+        for (message, reference) in cases {
+            assert_eq!(
+                squash_pr_reference(message).as_deref(),
+                reference,
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    fn detect_merge_commits() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        assert_eq!(
+            integration_style(&repo.repo, &GitflowConfig::default()).unwrap(),
+            None
+        );
+        repo.branch("feature/login");
+        repo.commit("login page");
+        repo.checkout("develop");
+        repo.merge("feature/login", "Merge branch 'feature/login' into develop");
+        assert_eq!(
+            integration_style(&repo.repo, &GitflowConfig::default()).unwrap(),
+            Some(IntegrationStyle::MergeCommit)
+        );
+    }
+
+    #[test]
+    fn detect_squash_merges() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("Add a login page (#12)");
+        repo.commit("Fix typo");
+        repo.commit("Add a logout button (#13)");
+        assert_eq!(
+            integration_style(&repo.repo, &GitflowConfig::default()).unwrap(),
+            Some(IntegrationStyle::SquashMerge)
+        );
+    }
+
+    #[test]
+    fn preview_develop_merge() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.2.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("feature/login");
+        repo.commit("login page");
+        repo.commit("login tests");
+        repo.checkout("develop");
+        repo.commit("other feature");
+        repo.checkout("feature/login");
+
+        assert_eq!(
+            project_develop_merge(&repo.repo, &GitflowConfig::default()).unwrap(),
+            VersionInfo::Development
+        );
+        let preview = project_develop_merge_info(&repo.repo, &GitflowConfig::default()).unwrap();
+        assert_eq!(preview.version, VersionInfo::Development);
+        assert_eq!(preview.branch_name, "develop");
+        assert_eq!(preview.build_number, 6);
+        assert_eq!(preview.dev_version.as_deref(), Some("v1.3.0-dev.6"));
+        assert_eq!(preview.merged_branch.as_deref(), Some("feature/login"));
+        let calendar = GitflowConfig {
+            dev_version_scheme: crate::DevVersionScheme::Calendar,
+            ..Default::default()
+        };
+        let calendar_preview = project_develop_merge_info(&repo.repo, &calendar).unwrap();
+        assert_eq!(
+            calendar_preview.dev_version.as_deref(),
+            Some("2020.09.13-dev.6")
+        );
+
+        // The preview matches actually merging
+        repo.checkout("develop");
+        repo.merge("feature/login", "Merge branch 'feature/login' into develop");
+        let merged = crate::get_info_from_path(repo.dir.path()).unwrap();
+        assert_eq!(merged.build_number, preview.build_number);
+        assert_eq!(merged.dev_version, preview.dev_version);
+        assert_eq!(merged.merged_branch, preview.merged_branch);
+        let merged = crate::get_info_with_config(repo.dir.path(), &calendar).unwrap();
+        assert_eq!(merged.dev_version, calendar_preview.dev_version);
+
+        // Nothing left to merge
+        repo.checkout("feature/login");
+        assert_eq!(
+            project_develop_merge_info(&repo.repo, &GitflowConfig::default())
+                .unwrap()
+                .build_number,
+            6
+        );
+    }
+
+    #[test]
+    fn projected_production_of_release_merge() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.1.0");
+        repo.commit("fix");
+        repo.checkout("main");
+        repo.commit("docs");
+        assert!(matches!(
+            projected_production(&repo.repo, &GitflowConfig::default()),
+            Err(GitflowError::NotReleaseBranch { branch }) if branch == "main"
+        ));
+
+        let v1_1_0 = SemverBase {
+            major: 1,
+            minor: 1,
+            patch: 0,
+        };
+        repo.merge("release/v1.1.0", "Merge branch 'release/v1.1.0'");
+        assert_eq!(
+            projected_production(&repo.repo, &GitflowConfig::default()).unwrap(),
+            v1_1_0
+        );
+        repo.tag("v1.1.0");
+        assert_eq!(
+            projected_production(&repo.repo, &GitflowConfig::default()).unwrap(),
+            v1_1_0
+        );
+
+        // Merged without the default message, found by the branch at the merged parent
+        repo.checkout("develop");
+        repo.branch("hotfix/v1.1.1");
+        repo.commit("urgent fix");
+        repo.checkout("main");
+        repo.merge("hotfix/v1.1.1", "Ship it");
+        assert_eq!(
+            projected_production(&repo.repo, &GitflowConfig::default()).unwrap(),
+            SemverBase {
+                major: 1,
+                minor: 1,
+                patch: 1,
+            }
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Audit records of computed versions stored as git notes
+
+use crate::{GitflowConfig, GitflowError, GitflowInfo};
+use git2::Repository;
+
+/// What [`write_version_note`] does when the commit already has a note
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum NoteConflict {
+    /// Replace the note, keeping only the latest build
+    Overwrite,
+
+    /// Add the version as a new line, keeping a record of every build of the commit
+    Append,
+}
+
+/// Records the version `info` was built as in a git note on HEAD, under
+/// [`GitflowConfig::notes_ref`], leaving a durable audit trail of build versions. The note is the
+/// [`GitflowInfo::stamp`] of the build. An existing note is handled as
+/// [`GitflowConfig::note_conflict`] says.
+///
+/// Nothing is written if [`GitflowConfig::dry_run`] is set. The note is signed with the
+/// repository's configured `user.name` and `user.email`, which must be set. Notes aren't pushed
+/// with branches, push the notes ref explicitly to share them.
+pub fn write_version_note(
+    repo: &Repository,
+    info: &GitflowInfo,
+    config: &GitflowConfig,
+) -> Result<(), GitflowError> {
+    let commit = repo.head()?.peel_to_commit()?.id();
+    let stamp = info.stamp();
+    if config.dry_run {
+        #[cfg(feature = "log")]
+        log::info!(
+            "Dry run, not writing note {:?} to {} on {}",
+            stamp,
+            config.notes_ref,
+            crate::format_hash(commit, config)
+        );
+        return Ok(());
+    }
+    let message = match (
+        config.note_conflict,
+        repo.find_note(Some(&config.notes_ref), commit),
+    ) {
+        (NoteConflict::Append, Ok(note)) => match note.message() {
+            Some(existing) if !existing.is_empty() => {
+                format!("{}\n{}", existing.trim_end_matches('\n'), stamp)
+            }
+            _ => stamp,
+        },
+        _ => stamp,
+    };
+    let signature = repo.signature()?;
+    repo.note(
+        &signature,
+        &signature,
+        Some(&config.notes_ref),
+        commit,
+        &message,
+        true,
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{get_info_for_commit, test_util::TestRepo};
+    use git2::Oid;
+
+    fn note(repo: &TestRepo, commit: Oid) -> Option<String> {
+        repo.repo
+            .find_note(Some("refs/notes/gitflow"), commit)
+            .ok()
+            .and_then(|note| note.message().map(str::to_owned))
+    }
+
+    #[test]
+    fn version_notes() {
+        let repo = TestRepo::new();
+        let mut git_config = repo.repo.config().unwrap();
+        git_config.set_str("user.name", "Builder").unwrap();
+        git_config
+            .set_str("user.email", "builder@example.com")
+            .unwrap();
+        repo.commit("initial");
+        repo.branch("develop");
+        let commit = repo.commit("feature");
+        let mut config = GitflowConfig {
+            dry_run: true,
+            ..Default::default()
+        };
+        let info = get_info_for_commit(&repo.repo, commit, "develop", &config).unwrap();
+        let stamp = info.stamp();
+
+        write_version_note(&repo.repo, &info, &config).unwrap();
+        assert_eq!(note(&repo, commit), None);
+
+        config.dry_run = false;
+        write_version_note(&repo.repo, &info, &config).unwrap();
+        assert_eq!(note(&repo, commit), Some(stamp.clone()));
+        write_version_note(&repo.repo, &info, &config).unwrap();
+        assert_eq!(note(&repo, commit), Some(stamp.clone()));
+
+        config.note_conflict = NoteConflict::Append;
+        write_version_note(&repo.repo, &info, &config).unwrap();
+        assert_eq!(note(&repo, commit), Some(format!("{}\n{}", stamp, stamp)));
+
+        // The note goes on HEAD however short the hash of the info is
+        let config = GitflowConfig {
+            hash_length: Some(4),
+            ..Default::default()
+        };
+        let commit = repo.commit("another feature");
+        let info = get_info_for_commit(&repo.repo, commit, "develop", &config).unwrap();
+        assert_eq!(info.commit_hash.len(), 4);
+        write_version_note(&repo.repo, &info, &config).unwrap();
+        assert_eq!(note(&repo, commit), Some(info.stamp()));
+    }
+}
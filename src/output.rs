@@ -0,0 +1,681 @@
+//! Renderings of [`GitflowInfo`] for consumption by other build systems
+
+use crate::{short_hash, Channel, GitflowError, GitflowInfo, SemverBase, VersionInfo};
+use git2::{ObjectType, Oid};
+use std::path::Path;
+
+/// Separators for [`VersionInfo::format_with_separators`], for artifact systems that don't accept
+/// the dots and dashes of semver
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct SeparatorConfig {
+    /// Placed between the major, minor, and patch numbers
+    pub version: String,
+
+    /// Placed between the version and `rc`, and between `rc` and the release candidate number
+    pub prerelease: String,
+}
+
+/// How [`GitflowInfo::format_with_channel_suffix`] appends the channel to a version, for artifact
+/// names that should say which channel they were built for
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct ChannelSuffix {
+    /// Placed between the version and the channel label, such as `-` or `+`
+    pub separator: String,
+
+    /// Label of production builds, `None` to leave releases without a suffix
+    pub production: Option<String>,
+
+    /// Label of alpha builds
+    pub alpha: String,
+
+    /// Label of development builds
+    pub development: String,
+
+    /// Label of local builds
+    pub local: String,
+}
+
+impl Default for ChannelSuffix {
+    /// `-` followed by `alpha`, `dev`, or `local`, with no suffix for production
+    fn default() -> Self {
+        Self {
+            separator: "-".to_owned(),
+            production: None,
+            alpha: "alpha".to_owned(),
+            development: "dev".to_owned(),
+            local: "local".to_owned(),
+        }
+    }
+}
+
+impl VersionInfo {
+    /// Writes a production or alpha version with the separators of `separators` and without the
+    /// `v` prefix, e.g. `1_2_3` or `1.2.3_rc_4`. Development and local builds have no version and
+    /// give `None`.
+    pub fn format_with_separators(&self, separators: &SeparatorConfig) -> Option<String> {
+        let base = |base: &SemverBase| {
+            [base.major, base.minor, base.patch]
+                .map(|part| part.to_string())
+                .join(&separators.version)
+        };
+        match &self {
+            VersionInfo::Production(semver) => Some(base(semver)),
+            VersionInfo::Alpha(semver) => Some(format!(
+                "{}{sep}rc{sep}{}",
+                base(&semver.base),
+                semver.rc,
+                sep = separators.prerelease
+            )),
+            VersionInfo::Development | VersionInfo::Local => None,
+        }
+    }
+}
+
+impl GitflowInfo {
+    /// The semver version for production and alpha builds, or the
+    /// [`dev_version`](GitflowInfo::dev_version) otherwise
+    fn semver_or_dev_version(&self) -> Option<String> {
+        self.version
+            .get_semver()
+            .or_else(|| self.dev_version.clone())
+    }
+
+    /// The version as [`GitflowInfo::semver_or_dev_version`] gives it, or the channel name for
+    /// builds with neither
+    fn version_or_channel(&self) -> String {
+        self.semver_or_dev_version()
+            .unwrap_or_else(|| self.version.channel().to_string())
+    }
+
+    /// Writes the version followed by the channel as `suffix` says, e.g. `v1.2.3-rc.4-alpha` or
+    /// `v1.3.0-dev.42+dev`. The version is the semver version of production and alpha builds and
+    /// the [`dev_version`](GitflowInfo::dev_version) of other builds, or just the channel label
+    /// without a separator if they have none. This is independent of the semver prerelease, which
+    /// stays part of the version.
+    pub fn format_with_channel_suffix(&self, suffix: &ChannelSuffix) -> String {
+        let label = match self.version.channel() {
+            Channel::Production => match &suffix.production {
+                Some(label) => label,
+                None => return self.version_or_channel(),
+            },
+            Channel::Alpha => &suffix.alpha,
+            Channel::Development => &suffix.development,
+            Channel::Local => &suffix.local,
+        };
+        match self.semver_or_dev_version() {
+            Some(version) => format!("{}{}{}", version, suffix.separator, label),
+            None => label.clone(),
+        }
+    }
+
+    /// Names this build's deployment to `environment` for GitOps tooling, as
+    /// `<environment>-<version>`, e.g. `prod-v1.2.3` or `staging-v1.3.0-rc.2`:
+    ///
+    /// - `environment` is lowercased, with every run of characters other than ASCII letters and
+    ///   digits replaced by a single `-`, and leading and trailing `-` removed, so `QA East` is
+    ///   `qa-east`. An environment with nothing left gives just the version.
+    /// - `version` is the semver version of production and alpha builds and the
+    ///   [`dev_version`](GitflowInfo::dev_version) of other builds, or the channel name, such as
+    ///   `development`, if they have none
+    ///
+    /// The result only uses the characters `[a-z0-9.-]` plus any in the version, so it is a
+    /// valid git tag and container image tag, and the same build and environment always give the
+    /// same tag.
+    pub fn deployment_tag(&self, environment: &str) -> String {
+        let mut slug = String::new();
+        for c in environment.chars() {
+            if c.is_ascii_alphanumeric() {
+                slug.push(c.to_ascii_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = slug.trim_end_matches('-');
+        let version = self.version_or_channel();
+        if slug.is_empty() {
+            version
+        } else {
+            format!("{}-{}", slug, version)
+        }
+    }
+
+    /// Renders a single line stamp suitable for writing to a `version.txt` artifact:
+    ///
+    /// ```text
+    /// <channel> <version> <short-sha> <build>
+    /// ```
+    ///
+    /// - `channel` is one of `production`, `alpha`, `development`, or `local`
+    /// - `version` is the semver version such as `v1.2.3` or `v1.2.3-rc.4`, or `-` for
+    ///   development and local builds, which have no version
+    /// - `short-sha` is the first 7 characters of the commit hash, or all of it if
+    ///   [`GitflowConfig::hash_length`](crate::GitflowConfig::hash_length) abbreviated it to fewer
+    /// - `build` is the build number in decimal
+    ///
+    /// Fields are separated by a single space and there is no trailing newline. Unlike the
+    /// [`Display`](std::fmt::Display) impls, this format is frozen and will not change between
+    /// releases of this crate, so tools can rely on parsing it.
+    pub fn stamp(&self) -> String {
+        format!(
+            "{} {} {} {}",
+            self.version.channel(),
+            self.version.get_semver().as_deref().unwrap_or("-"),
+            short_hash(&self.commit_hash),
+            self.build_number
+        )
+    }
+
+    /// Computes an identifier of the exact inputs of a build, the source tree and the resolved
+    /// dependencies in `cargo_lock`, for cache keys that should only change when either does.
+    /// Commit messages, authors, and dates don't affect it.
+    ///
+    /// The identifier is the git blob hash (SHA-1 over `blob <len>\0<content>`) of the manifest
+    ///
+    /// ```text
+    /// tree <tree hash>
+    /// cargo-lock <git blob hash of the lockfile>
+    /// ```
+    ///
+    /// with each line ending in `\n`, written as 40 lowercase hex characters. The lockfile is
+    /// hashed byte for byte.
+    pub fn build_identity(&self, cargo_lock: &Path) -> Result<String, GitflowError> {
+        let lock = std::fs::read(cargo_lock).map_err(GitflowError::Io)?;
+        let lock = Oid::hash_object(ObjectType::Blob, &lock)?;
+        let manifest = format!("tree {}\ncargo-lock {}\n", self.tree_hash, lock);
+        Ok(Oid::hash_object(ObjectType::Blob, manifest.as_bytes())?.to_string())
+    }
+
+    /// Renders this info as a Java `.properties` file, escaped so that
+    /// `java.util.Properties::load` reads back the exact values. The version is the semver
+    /// version, falling back to the development version and then the channel name.
+    pub fn to_java_properties(&self) -> String {
+        let properties = [
+            ("gitflow.version", self.version_or_channel()),
+            ("gitflow.channel", self.version.channel().to_string()),
+            ("gitflow.branch", self.branch_name.clone()),
+            ("gitflow.commit", self.commit_hash.clone()),
+            ("gitflow.build", self.build_number.to_string()),
+        ];
+        let mut out = String::new();
+        for (key, value) in properties {
+            out.push_str(&escape_property(key, true));
+            out.push('=');
+            out.push_str(&escape_property(&value, false));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Renders this info in the `KEY value` lines Bazel reads from a `--workspace_status_command`:
+    ///
+    /// ```text
+    /// STABLE_GITFLOW_VERSION v1.2.3
+    /// STABLE_GITFLOW_CHANNEL production
+    /// STABLE_GITFLOW_BRANCH main
+    /// STABLE_GITFLOW_COMMIT 0123456789abcdef0123456789abcdef01234567
+    /// STABLE_GITFLOW_BUILD 57
+    /// GITFLOW_COMMIT_TIME 1600000000
+    /// ```
+    ///
+    /// `STABLE_` keys identify what is being built, so Bazel reruns stamped actions when they
+    /// change. The commit time is volatile, as it changes with amends that leave the build as is.
+    /// The version is the semver version, falling back to the development version and then the
+    /// channel name. Keys whose value isn't known, like the branch of a detached build, are left
+    /// out. Each line ends in `\n`.
+    pub fn to_bazel_status(&self) -> String {
+        let version = self.version_or_channel();
+        let status = [
+            ("STABLE_GITFLOW_VERSION", version),
+            ("STABLE_GITFLOW_CHANNEL", self.version.channel().to_string()),
+            ("STABLE_GITFLOW_BRANCH", self.branch_name.clone()),
+            ("STABLE_GITFLOW_COMMIT", self.commit_hash.clone()),
+            ("STABLE_GITFLOW_BUILD", self.build_number.to_string()),
+            ("GITFLOW_COMMIT_TIME", self.commit_time.to_string()),
+        ];
+        let mut out = String::new();
+        for (key, value) in status {
+            if !value.is_empty() {
+                out.push_str(&format!("{} {}\n", key, value));
+            }
+        }
+        out
+    }
+
+    /// Renders the summary of this build to serve from a `/version` or health endpoint:
+    ///
+    /// ```json
+    /// {
+    ///   "version": "v1.2.3",
+    ///   "commit": "0123456789abcdef0123456789abcdef01234567",
+    ///   "branch": "main",
+    ///   "channel": "production",
+    ///   "build": 57
+    /// }
+    /// ```
+    ///
+    /// `version` is the semver version, falling back to the development version, and is `null`
+    /// for builds with neither. `channel` is as in [`GitflowInfo::stamp`].
+    ///
+    /// Unlike [`GitflowInfo::to_json`], which mirrors every field of this struct, this shape is
+    /// stable: these keys will not be renamed or removed and their values keep their types, so
+    /// services can expose it directly.
+    #[cfg(feature = "json")]
+    pub fn health_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "version": self.semver_or_dev_version(),
+            "commit": self.commit_hash,
+            "branch": self.branch_name,
+            "channel": self.version.channel().to_string(),
+            "build": self.build_number,
+        })
+    }
+
+    /// Renders this build as a minimal [CycloneDX] component named `name`, for adding to a
+    /// software bill of materials:
+    ///
+    /// ```json
+    /// {
+    ///   "type": "application",
+    ///   "name": "my-service",
+    ///   "version": "v1.2.3",
+    ///   "pedigree": {
+    ///     "commits": [{ "uid": "0123456789abcdef0123456789abcdef01234567" }]
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// `version` is the semver version, falling back to the development version and then the
+    /// channel name, as CycloneDX requires a string. The commit is recorded in the component's
+    /// pedigree, which is left out when the commit isn't known.
+    ///
+    /// [CycloneDX]: https://cyclonedx.org/docs/1.5/json/#components
+    #[cfg(feature = "json")]
+    pub fn sbom_component(&self, name: &str) -> serde_json::Value {
+        let version = self.version_or_channel();
+        let mut component = serde_json::json!({
+            "type": "application",
+            "name": name,
+            "version": version,
+        });
+        if !self.commit_hash.is_empty() {
+            component["pedigree"] = serde_json::json!({
+                "commits": [{ "uid": self.commit_hash }],
+            });
+        }
+        component
+    }
+
+    /// Renders this info as Rust source declaring the constants the [`built`] crate generates
+    /// for git, so that `built`'s git support can be replaced without changing the code that reads
+    /// them. The generated file is meant to be written to `OUT_DIR` by a build script and
+    /// `include!`d. The mapping is:
+    ///
+    /// - `PKG_VERSION: &str` is the semver version without the leading `v`, falling back to the
+    ///   development version and then the channel name for builds without one
+    /// - `GIT_COMMIT_HASH: Option<&str>` is the full commit hash
+    /// - `GIT_HEAD_REF: Option<&str>` is `refs/heads/<branch>`
+    ///
+    /// The optional constants are `None` when the value isn't known, such as for an info made by
+    /// [`GitflowInfo::from_version_string`].
+    ///
+    /// [`built`]: https://docs.rs/built
+    #[cfg(feature = "built-compat")]
+    pub fn to_built_constants(&self) -> String {
+        let version = self.version_or_channel();
+        let version = version.strip_prefix('v').unwrap_or(&version);
+        let optional = |value: Option<String>| match value {
+            Some(value) => format!("Some({:?})", value),
+            None => "None".to_owned(),
+        };
+        let commit = Some(self.commit_hash.clone()).filter(|hash| !hash.is_empty());
+        let head_ref = Some(&self.branch_name)
+            .filter(|branch| !branch.is_empty())
+            .map(|branch| format!("refs/heads/{}", branch));
+        format!(
+            "pub const PKG_VERSION: &str = {:?};\n\
+             pub const GIT_COMMIT_HASH: Option<&str> = {};\n\
+             pub const GIT_HEAD_REF: Option<&str> = {};\n",
+            version,
+            optional(commit),
+            optional(head_ref)
+        )
+    }
+}
+
+/// Escapes a key or value per the `java.util.Properties` file format. Non ASCII characters are
+/// written as `\uXXXX` UTF-16 escapes since `.properties` files are read as ISO 8859-1.
+fn escape_property(s: &str, is_key: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for (i, c) in s.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '=' | ':' | '#' | '!' => {
+                out.push('\\');
+                out.push(c);
+            }
+            // Spaces only need escaping where they would otherwise be trimmed or end the key
+            ' ' if is_key || i == 0 => out.push_str("\\ "),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\x0c' => out.push_str("\\f"),
+            ' '..='~' => out.push(c),
+            _ => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    out.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util, SemverBase, SemverRC, VersionInfo};
+
+    #[test]
+    fn stamp_format() {
+        let info = test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            57,
+        );
+        assert_eq!(info.stamp(), "production v1.2.3 0123456 57");
+        let info = test_util::info(VersionInfo::Development, 8);
+        assert_eq!(info.stamp(), "development - 0123456 8");
+    }
+
+    #[test]
+    fn custom_separators() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        let release = VersionInfo::Production(base);
+        let rc = VersionInfo::Alpha(SemverRC { base, rc: 4 });
+
+        let underscores = SeparatorConfig {
+            version: ".".to_owned(),
+            prerelease: "_".to_owned(),
+        };
+        assert_eq!(
+            release.format_with_separators(&underscores).as_deref(),
+            Some("1.2.3")
+        );
+        assert_eq!(
+            rc.format_with_separators(&underscores).as_deref(),
+            Some("1.2.3_rc_4")
+        );
+
+        let dashes = SeparatorConfig {
+            version: "-".to_owned(),
+            prerelease: "".to_owned(),
+        };
+        assert_eq!(
+            release.format_with_separators(&dashes).as_deref(),
+            Some("1-2-3")
+        );
+        assert_eq!(
+            rc.format_with_separators(&dashes).as_deref(),
+            Some("1-2-3rc4")
+        );
+        assert_eq!(VersionInfo::Local.format_with_separators(&dashes), None);
+    }
+
+    #[test]
+    fn channel_suffixes() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        let release = test_util::info(VersionInfo::Production(base), 10);
+        let rc = test_util::info(VersionInfo::Alpha(SemverRC { base, rc: 4 }), 11);
+        let mut dev = test_util::info(VersionInfo::Development, 12);
+        dev.dev_version = Some("v1.3.0-dev.12".to_owned());
+        let local = test_util::info(VersionInfo::Local, 13);
+
+        let dashes = ChannelSuffix::default();
+        assert_eq!(release.format_with_channel_suffix(&dashes), "v1.2.3");
+        assert_eq!(rc.format_with_channel_suffix(&dashes), "v1.2.3-rc.4-alpha");
+        assert_eq!(dev.format_with_channel_suffix(&dashes), "v1.3.0-dev.12-dev");
+        assert_eq!(local.format_with_channel_suffix(&dashes), "local");
+
+        let build_metadata = ChannelSuffix {
+            separator: "+".to_owned(),
+            production: Some("stable".to_owned()),
+            alpha: "beta".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(
+            release.format_with_channel_suffix(&build_metadata),
+            "v1.2.3+stable"
+        );
+        assert_eq!(
+            rc.format_with_channel_suffix(&build_metadata),
+            "v1.2.3-rc.4+beta"
+        );
+        assert_eq!(
+            dev.format_with_channel_suffix(&build_metadata),
+            "v1.3.0-dev.12+dev"
+        );
+    }
+
+    #[test]
+    fn deployment_tags() {
+        let base = SemverBase {
+            major: 1,
+            minor: 2,
+            patch: 3,
+        };
+        let release = test_util::info(VersionInfo::Production(base), 10);
+        let rc = test_util::info(VersionInfo::Alpha(SemverRC { base, rc: 2 }), 11);
+        let mut dev = test_util::info(VersionInfo::Development, 12);
+        dev.dev_version = Some("v1.3.0-dev.12".to_owned());
+        let local = test_util::info(VersionInfo::Local, 13);
+
+        assert_eq!(release.deployment_tag("prod"), "prod-v1.2.3");
+        assert_eq!(rc.deployment_tag("staging"), "staging-v1.2.3-rc.2");
+        assert_eq!(dev.deployment_tag("dev"), "dev-v1.3.0-dev.12");
+        assert_eq!(local.deployment_tag("preview"), "preview-local");
+        assert_eq!(release.deployment_tag(" QA East/2 "), "qa-east-2-v1.2.3");
+        assert_eq!(
+            rc.deployment_tag("--Staging__EU--"),
+            "staging-eu-v1.2.3-rc.2"
+        );
+        assert_eq!(release.deployment_tag("//"), "v1.2.3");
+    }
+
+    #[test]
+    fn bazel_status() {
+        let info = test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            57,
+        );
+        assert_eq!(
+            info.to_bazel_status(),
+            "STABLE_GITFLOW_VERSION v1.2.3\n\
+             STABLE_GITFLOW_CHANNEL production\n\
+             STABLE_GITFLOW_BRANCH main\n\
+             STABLE_GITFLOW_COMMIT 0123456789abcdef0123456789abcdef01234567\n\
+             STABLE_GITFLOW_BUILD 57\n\
+             GITFLOW_COMMIT_TIME 1600000000\n"
+        );
+
+        let mut info = test_util::info(VersionInfo::Development, 8);
+        info.branch_name = String::new();
+        info.dev_version = Some("v1.3.0-dev.8".to_owned());
+        assert_eq!(
+            info.to_bazel_status(),
+            "STABLE_GITFLOW_VERSION v1.3.0-dev.8\n\
+             STABLE_GITFLOW_CHANNEL development\n\
+             STABLE_GITFLOW_COMMIT 0123456789abcdef0123456789abcdef01234567\n\
+             STABLE_GITFLOW_BUILD 8\n\
+             GITFLOW_COMMIT_TIME 1600000000\n"
+        );
+    }
+
+    #[test]
+    fn build_identity() {
+        let dir = tempfile::tempdir().unwrap();
+        let lock = dir.path().join("Cargo.lock");
+        std::fs::write(&lock, "version = 3\n").unwrap();
+        let mut info = test_util::info(VersionInfo::Development, 1);
+        // printf 'tree 89ab...\ncargo-lock 08d9...\n' | git hash-object --stdin
+        let identity = "f44b6dda30fe58cc1a9878a7dbfde1a8c5330289";
+        assert_eq!(info.build_identity(&lock).unwrap(), identity);
+
+        // Only the tree and the lockfile matter
+        info.commit_hash = "fedcba9876543210fedcba9876543210fedcba98".to_owned();
+        info.build_number = 2;
+        assert_eq!(info.build_identity(&lock).unwrap(), identity);
+        info.tree_hash = "0000000000000000000000000000000000000000".to_owned();
+        assert_ne!(info.build_identity(&lock).unwrap(), identity);
+
+        let info = test_util::info(VersionInfo::Development, 1);
+        std::fs::write(&lock, "version = 4\n").unwrap();
+        assert_ne!(info.build_identity(&lock).unwrap(), identity);
+        assert!(info.build_identity(&dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn java_properties() {
+        let mut info = test_util::info(
+            VersionInfo::Alpha(SemverRC {
+                base: SemverBase {
+                    major: 1,
+                    minor: 2,
+                    patch: 3,
+                },
+                rc: 4,
+            }),
+            57,
+        );
+        info.branch_name = " release/v1.2.3 =:#!\\ü😀".to_owned();
+        assert_eq!(
+            info.to_java_properties(),
+            "gitflow.version=v1.2.3-rc.4\n\
+             gitflow.channel=alpha\n\
+             gitflow.branch=\\ release/v1.2.3 \\=\\:\\#\\!\\\\\\u00FC\\uD83D\\uDE00\n\
+             gitflow.commit=0123456789abcdef0123456789abcdef01234567\n\
+             gitflow.build=57\n"
+        );
+    }
+
+    #[test]
+    fn java_properties_development() {
+        let mut info = test_util::info(VersionInfo::Development, 3);
+        assert!(info
+            .to_java_properties()
+            .starts_with("gitflow.version=development\n"));
+        info.dev_version = Some("v1.3.0-dev.3".to_owned());
+        assert!(info
+            .to_java_properties()
+            .starts_with("gitflow.version=v1.3.0-dev.3\n"));
+    }
+
+    #[test]
+    fn escape_property_keys() {
+        assert_eq!(escape_property("a b", true), "a\\ b");
+        assert_eq!(escape_property("a b", false), "a b");
+        assert_eq!(escape_property("\t\n\r", false), "\\t\\n\\r");
+    }
+
+    #[test]
+    #[cfg(feature = "built-compat")]
+    fn built_constants() {
+        let info = test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            57,
+        );
+        assert_eq!(
+            info.to_built_constants(),
+            "pub const PKG_VERSION: &str = \"1.2.3\";\n\
+             pub const GIT_COMMIT_HASH: Option<&str> = \
+             Some(\"0123456789abcdef0123456789abcdef01234567\");\n\
+             pub const GIT_HEAD_REF: Option<&str> = Some(\"refs/heads/main\");\n"
+        );
+
+        let info = GitflowInfo::from_version_string("v1.2.3-rc.4").unwrap();
+        assert_eq!(
+            info.to_built_constants(),
+            "pub const PKG_VERSION: &str = \"1.2.3-rc.4\";\n\
+             pub const GIT_COMMIT_HASH: Option<&str> = None;\n\
+             pub const GIT_HEAD_REF: Option<&str> = None;\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn health_json() {
+        let info = test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            57,
+        );
+        assert_eq!(
+            info.health_json().to_string(),
+            "{\"branch\":\"main\",\"build\":57,\"channel\":\"production\",\
+             \"commit\":\"0123456789abcdef0123456789abcdef01234567\",\"version\":\"v1.2.3\"}"
+        );
+
+        let mut info = test_util::info(VersionInfo::Development, 8);
+        assert_eq!(info.health_json()["version"], serde_json::Value::Null);
+        info.dev_version = Some("v1.3.0-dev.8".to_owned());
+        assert_eq!(info.health_json()["version"], "v1.3.0-dev.8");
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn sbom_component() {
+        let info = test_util::info(
+            VersionInfo::Production(SemverBase {
+                major: 1,
+                minor: 2,
+                patch: 3,
+            }),
+            57,
+        );
+        assert_eq!(
+            info.sbom_component("my-service"),
+            serde_json::json!({
+                "type": "application",
+                "name": "my-service",
+                "version": "v1.2.3",
+                "pedigree": {
+                    "commits": [{ "uid": "0123456789abcdef0123456789abcdef01234567" }],
+                },
+            })
+        );
+
+        let info = GitflowInfo::from_version_string("v1.2.3-rc.4").unwrap();
+        assert_eq!(
+            info.sbom_component("my-service"),
+            serde_json::json!({
+                "type": "application",
+                "name": "my-service",
+                "version": "v1.2.3-rc.4",
+            })
+        );
+        let info = test_util::info(VersionInfo::Local, 3);
+        assert_eq!(info.sbom_component("my-service")["version"], "local");
+    }
+}
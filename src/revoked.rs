@@ -0,0 +1,97 @@
+//! Detection of releases that have been revoked after they were published
+
+use crate::{GitflowConfig, GitflowError, SemverBase};
+use git2::Repository;
+
+/// How a published release is marked as revoked, see [`is_revoked`]
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum RevocationMarker {
+    /// A tag named after the release with this suffix, such as `v1.2.3-revoked` for the default
+    /// suffix `-revoked`. The tag can point anywhere, usually at the revoked release.
+    TagSuffix(String),
+
+    /// A git note in this notes ref on the commit tagged with the release, such as one added with
+    /// `git notes --ref revoked add -m "reason" v1.2.3`. Any note marks the release revoked, its
+    /// message can record why.
+    NotesRef(String),
+}
+
+impl Default for RevocationMarker {
+    fn default() -> Self {
+        Self::TagSuffix("-revoked".to_owned())
+    }
+}
+
+/// Whether the release `version` has been revoked, marked as not to be used by the
+/// [`GitflowConfig::revocation_marker`] convention. A running binary can check its own version
+/// this way to warn that it is a revoked build. A release without a production tag is never
+/// revoked by [`RevocationMarker::NotesRef`].
+pub fn is_revoked(
+    repo: &Repository,
+    version: &SemverBase,
+    config: &GitflowConfig,
+) -> Result<bool, GitflowError> {
+    match &config.revocation_marker {
+        RevocationMarker::TagSuffix(suffix) => {
+            let name = format!("refs/tags/{}{}", version, suffix);
+            Ok(repo.find_reference(&name).is_ok())
+        }
+        RevocationMarker::NotesRef(notes_ref) => {
+            let commit = match repo.revparse_single(&format!("refs/tags/{}", version)) {
+                Ok(tag) => tag.peel_to_commit()?.id(),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(false),
+                Err(err) => return Err(err.into()),
+            };
+            Ok(repo.find_note(Some(notes_ref), commit).is_ok())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+
+    #[test]
+    fn revoked_releases() {
+        let repo = TestRepo::new();
+        let first = repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("fix");
+        repo.tag("v1.0.1");
+        repo.tag("v1.0.1-revoked");
+        let v1_0_0 = SemverBase {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        };
+        let v1_0_1 = SemverBase {
+            major: 1,
+            minor: 0,
+            patch: 1,
+        };
+
+        let mut config = GitflowConfig::default();
+        assert!(!is_revoked(&repo.repo, &v1_0_0, &config).unwrap());
+        assert!(is_revoked(&repo.repo, &v1_0_1, &config).unwrap());
+
+        config.revocation_marker = RevocationMarker::NotesRef("refs/notes/revoked".to_owned());
+        assert!(!is_revoked(&repo.repo, &v1_0_1, &config).unwrap());
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let notes_ref = Some("refs/notes/revoked");
+        repo.repo
+            .note(&signature, &signature, notes_ref, first, "bad build", false)
+            .unwrap();
+        assert!(is_revoked(&repo.repo, &v1_0_0, &config).unwrap());
+        assert!(!is_revoked(
+            &repo.repo,
+            &SemverBase {
+                major: 2,
+                minor: 0,
+                patch: 0
+            },
+            &config
+        )
+        .unwrap());
+    }
+}
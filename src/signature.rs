@@ -0,0 +1,365 @@
+//! Extraction of the signer of signed release tags
+
+use crate::{GitflowConfig, GitflowError, TagIndex, VersionInfo};
+use git2::Repository;
+
+const PGP_BEGIN: &str = "-----BEGIN PGP SIGNATURE-----";
+const PGP_END: &str = "-----END PGP SIGNATURE-----";
+const SSH_BEGIN: &str = "-----BEGIN SSH SIGNATURE-----";
+const SSH_END: &str = "-----END SSH SIGNATURE-----";
+
+/// Returns the signer of the annotated production tag at HEAD, if it is signed.
+///
+/// For OpenPGP signatures this is the signer's user id when the signature records one, otherwise
+/// the issuer fingerprint or key id in upper case hex. Only version 4 OpenPGP signatures are
+/// understood. For SSH signatures this is the signing public key in OpenSSH `authorized_keys`
+/// format. Unsigned tags, lightweight tags, and other signature formats (such as X.509) give
+/// `None`.
+///
+/// The signature is **not** verified. It is only parsed far enough to find who claims to have
+/// made it, with no cryptographic verification, so the result must not be used as proof that a
+/// release was signed by anyone in particular. Use `git tag -v` or a verifying OpenPGP/SSH
+/// implementation for that.
+pub fn production_tag_signer(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Option<String>, GitflowError> {
+    let head = repo.head()?.peel_to_commit()?.id();
+    let tags = TagIndex::new(repo, config)?;
+    for tag in tags.at(head) {
+        if !matches!(tag.version, VersionInfo::Production(_)) {
+            continue;
+        }
+        let reference = repo.find_reference(&format!("refs/tags/{}", tag.name))?;
+        let annotated = match reference.target().and_then(|id| repo.find_tag(id).ok()) {
+            Some(annotated) => annotated,
+            None => continue,
+        };
+        if let Some(signer) = annotated.message_bytes().and_then(signer_from_message) {
+            return Ok(Some(signer));
+        }
+    }
+    Ok(None)
+}
+
+/// Finds the signature block at the end of a tag message and extracts its signer
+fn signer_from_message(message: &[u8]) -> Option<String> {
+    let message = String::from_utf8_lossy(message);
+    if let Some(body) = armored_body(&message, PGP_BEGIN, PGP_END) {
+        pgp_signer(&base64_decode(&body)?)
+    } else if let Some(body) = armored_body(&message, SSH_BEGIN, SSH_END) {
+        ssh_signer(&base64_decode(&body)?)
+    } else {
+        None
+    }
+}
+
+/// Returns the base64 payload between `begin` and `end`, skipping armor headers and the CRC line
+fn armored_body(message: &str, begin: &str, end: &str) -> Option<String> {
+    let start = message.find(begin)? + begin.len();
+    let stop = start + message[start..].find(end)?;
+    let block = &message[start..stop];
+    // Armor headers such as `Version:` are separated from the payload by a blank line
+    let payload = match block.find("\n\n") {
+        Some(split) if block[..split].contains(':') => &block[split..],
+        _ => block,
+    };
+    Some(
+        payload
+            .lines()
+            .map(str::trim)
+            .take_while(|line| !line.starts_with('='))
+            .collect(),
+    )
+}
+
+/// Extracts the signer from the first OpenPGP signature packet in `data`
+fn pgp_signer(data: &[u8]) -> Option<String> {
+    let (tag, body) = pgp_packet(data)?;
+    if tag != 2 || body.first() != Some(&4) {
+        return None;
+    }
+    // version, signature type, public key algorithm, hash algorithm, then the subpacket areas
+    let mut rest = body.get(4..)?;
+    let (mut user_id, mut fingerprint, mut key_id) = (None, None, None);
+    for _ in 0..2 {
+        let len = u16::from_be_bytes([*rest.first()?, *rest.get(1)?]) as usize;
+        let mut area = rest.get(2..2 + len)?;
+        rest = &rest[2 + len..];
+        while !area.is_empty() {
+            let (len, header) = match area[0] {
+                n @ 0..=191 => (n as usize, 1),
+                n @ 192..=254 => (((n as usize - 192) << 8) + *area.get(1)? as usize + 192, 2),
+                255 => (
+                    u32::from_be_bytes(area.get(1..5)?.try_into().ok()?) as usize,
+                    5,
+                ),
+            };
+            let subpacket = area.get(header..header + len)?;
+            area = &area[header + len..];
+            let (kind, data) = subpacket.split_first()?;
+            match kind & 0x7f {
+                16 => key_id = Some(hex::encode_upper(data)),
+                28 => user_id = Some(String::from_utf8_lossy(data).into_owned()),
+                33 => fingerprint = Some(hex::encode_upper(data.get(1..)?)),
+                _ => {}
+            }
+        }
+    }
+    user_id.or(fingerprint).or(key_id)
+}
+
+/// Splits the first OpenPGP packet in `data` into its tag and body
+fn pgp_packet(data: &[u8]) -> Option<(u8, &[u8])> {
+    let first = *data.first()?;
+    if first & 0x80 == 0 {
+        return None;
+    }
+    let (tag, len, header) = if first & 0x40 != 0 {
+        // New format packet header
+        let tag = first & 0x3f;
+        match *data.get(1)? {
+            n @ 0..=191 => (tag, n as usize, 2),
+            n @ 192..=223 => (
+                tag,
+                ((n as usize - 192) << 8) + *data.get(2)? as usize + 192,
+                3,
+            ),
+            255 => (
+                tag,
+                u32::from_be_bytes(data.get(2..6)?.try_into().ok()?) as usize,
+                6,
+            ),
+            _ => return None,
+        }
+    } else {
+        // Old format packet header
+        let tag = (first >> 2) & 0x0f;
+        match first & 0x03 {
+            0 => (tag, *data.get(1)? as usize, 2),
+            1 => (
+                tag,
+                u16::from_be_bytes([*data.get(1)?, *data.get(2)?]) as usize,
+                3,
+            ),
+            2 => (
+                tag,
+                u32::from_be_bytes(data.get(1..5)?.try_into().ok()?) as usize,
+                5,
+            ),
+            _ => (tag, data.len() - 1, 1),
+        }
+    };
+    Some((tag, data.get(header..header + len)?))
+}
+
+/// Extracts the public key from an `SSHSIG` blob as `<key type> <base64 key>`
+fn ssh_signer(data: &[u8]) -> Option<String> {
+    let rest = data.strip_prefix(b"SSHSIG")?;
+    let (public_key, _) = ssh_string(rest.get(4..)?)?;
+    let (key_type, _) = ssh_string(public_key)?;
+    Some(format!(
+        "{} {}",
+        String::from_utf8_lossy(key_type),
+        base64_encode(public_key)
+    ))
+}
+
+/// Reads a length prefixed string in the SSH wire format
+fn ssh_string(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let len = u32::from_be_bytes(data.get(..4)?.try_into().ok()?) as usize;
+    Some((data.get(4..4 + len)?, &data[4 + len..]))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace() && *c != b'=')
+    {
+        let value = BASE64.iter().position(|&b| b == c)? as u32;
+        buffer = buffer << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+
+    const FINGERPRINT: [u8; 20] = [0xab; 20];
+    const KEY_ID: [u8; 8] = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+
+    /// Builds a v4 OpenPGP signature packet with the given hashed and unhashed subpackets
+    fn pgp_signature(hashed: &[(u8, &[u8])], unhashed: &[(u8, &[u8])]) -> Vec<u8> {
+        let area = |subpackets: &[(u8, &[u8])]| {
+            let mut area = Vec::new();
+            for (kind, data) in subpackets {
+                area.push(data.len() as u8 + 1);
+                area.push(*kind);
+                area.extend_from_slice(data);
+            }
+            let mut out = (area.len() as u16).to_be_bytes().to_vec();
+            out.extend(area);
+            out
+        };
+        let mut body = vec![4, 0, 1, 8];
+        body.extend(area(hashed));
+        body.extend(area(unhashed));
+        // Hash prefix and a token signature MPI
+        body.extend([0xbe, 0xef, 0, 8, 0x42]);
+        let mut packet = vec![0xc2, body.len() as u8];
+        packet.extend(body);
+        packet
+    }
+
+    fn pgp_message(packet: &[u8]) -> String {
+        format!(
+            "Release v1.0.0\n{}\n\n{}\n=abcd\n{}\n",
+            PGP_BEGIN,
+            base64_encode(packet),
+            PGP_END
+        )
+    }
+
+    fn fingerprint_subpacket() -> Vec<u8> {
+        let mut data = vec![4];
+        data.extend(FINGERPRINT);
+        data
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(base64_decode(&base64_encode(data)).unwrap(), data);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fooba"), "Zm9vYmE=");
+    }
+
+    #[test]
+    fn pgp_fingerprint_preferred_over_key_id() {
+        let fingerprint = fingerprint_subpacket();
+        let packet = pgp_signature(&[(33, &fingerprint)], &[(16, &KEY_ID)]);
+        assert_eq!(
+            signer_from_message(pgp_message(&packet).as_bytes()).unwrap(),
+            hex::encode_upper(FINGERPRINT)
+        );
+
+        let packet = pgp_signature(&[], &[(16, &KEY_ID)]);
+        assert_eq!(
+            signer_from_message(pgp_message(&packet).as_bytes()).unwrap(),
+            "123456789ABCDEF0"
+        );
+    }
+
+    #[test]
+    fn pgp_user_id() {
+        let fingerprint = fingerprint_subpacket();
+        let packet = pgp_signature(
+            &[
+                (33, &fingerprint),
+                (28, b"Release Bot <release@example.com>"),
+            ],
+            &[(16, &KEY_ID)],
+        );
+        assert_eq!(
+            signer_from_message(pgp_message(&packet).as_bytes()).unwrap(),
+            "Release Bot <release@example.com>"
+        );
+    }
+
+    #[test]
+    fn ssh_public_key() {
+        let mut key = Vec::new();
+        key.extend(11u32.to_be_bytes());
+        key.extend(b"ssh-ed25519");
+        key.extend(32u32.to_be_bytes());
+        key.extend([7; 32]);
+        let mut blob = b"SSHSIG".to_vec();
+        blob.extend(1u32.to_be_bytes());
+        blob.extend((key.len() as u32).to_be_bytes());
+        blob.extend(&key);
+        blob.extend(3u32.to_be_bytes());
+        blob.extend(b"git");
+        let message = format!(
+            "v1.0.0\n{}\n{}\n{}\n",
+            SSH_BEGIN,
+            base64_encode(&blob),
+            SSH_END
+        );
+        assert_eq!(
+            signer_from_message(message.as_bytes()).unwrap(),
+            format!("ssh-ed25519 {}", base64_encode(&key))
+        );
+    }
+
+    #[test]
+    fn unsigned_and_malformed() {
+        assert_eq!(signer_from_message(b"Release v1.0.0\n"), None);
+        let message = format!("{}\n\n!!!\n{}\n", PGP_BEGIN, PGP_END);
+        assert_eq!(signer_from_message(message.as_bytes()), None);
+        let message = format!("{}\n\nwg==\n{}\n", PGP_BEGIN, PGP_END);
+        assert_eq!(signer_from_message(message.as_bytes()), None);
+    }
+
+    #[test]
+    fn signer_of_head_tag() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v0.9.0");
+        assert_eq!(
+            production_tag_signer(&repo.repo, &GitflowConfig::default()).unwrap(),
+            None
+        );
+
+        repo.commit("release");
+        let packet = pgp_signature(&[], &[(16, &KEY_ID)]);
+        let head = repo
+            .repo
+            .head()
+            .unwrap()
+            .peel(git2::ObjectType::Commit)
+            .unwrap();
+        let tagger = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.repo
+            .tag("v1.0.0", &head, &tagger, &pgp_message(&packet), false)
+            .unwrap();
+        assert_eq!(
+            production_tag_signer(&repo.repo, &GitflowConfig::default())
+                .unwrap()
+                .as_deref(),
+            Some("123456789ABCDEF0")
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Version stamps that carry the version of a build into source packages without a repository
+
+use crate::{get_info_with_config, GitflowConfig, GitflowError, GitflowInfo};
+use std::path::Path;
+
+/// File written at the root of a package by [`write_stamp`], and read by
+/// [`get_info_with_config`] in place of the repository when there is none
+pub const STAMP_FILE: &str = ".gitflow-stamp.json";
+
+impl GitflowInfo {
+    /// Serializes this info as pretty printed JSON, the format of the [`STAMP_FILE`]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("GitflowInfo always serializes")
+    }
+
+    /// Parses an info serialized with [`GitflowInfo::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, GitflowError> {
+        serde_json::from_str(json).map_err(|err| {
+            GitflowError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, err))
+        })
+    }
+}
+
+/// Determines the version of the repository at `path` and records it in the [`STAMP_FILE`] at
+/// `path`, returning the recorded info.
+///
+/// `cargo package` and `cargo publish` build from a tarball without `.git`, so a build script
+/// there can't see the repository. Run this in the release pipeline before packaging, not from a
+/// build script, as Cargo rejects build scripts that modify the package directory. The stamp is
+/// then packaged like any other file, so it must not be excluded by `.gitignore` or the
+/// `exclude` field of the manifest, and must be listed if the manifest has an `include` field.
+/// When the packaged crate is built, [`get_info_with_config`] finds no repository at `path` and
+/// reads the stamp instead.
+pub fn write_stamp(path: &Path, config: &GitflowConfig) -> Result<GitflowInfo, GitflowError> {
+    let info = get_info_with_config(path, config)?;
+    std::fs::write(path.join(STAMP_FILE), info.to_json()).map_err(GitflowError::Io)?;
+    Ok(info)
+}
+
+/// Reads the [`STAMP_FILE`] in `dir`, if there is one
+pub(crate) fn read_stamp(dir: &Path) -> Result<Option<GitflowInfo>, GitflowError> {
+    match std::fs::read_to_string(dir.join(STAMP_FILE)) {
+        Ok(json) => GitflowInfo::from_json(&json).map(Some),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(GitflowError::Io(err)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{get_info_from_path, test_util::TestRepo};
+
+    #[test]
+    fn stamp_only_directory() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.2.3");
+        let stamped = write_stamp(repo.dir.path(), &GitflowConfig::default()).unwrap();
+
+        // Simulates the package tarball, which has the stamp but no repository
+        let package = tempfile::tempdir().unwrap();
+        std::fs::copy(
+            repo.dir.path().join(STAMP_FILE),
+            package.path().join(STAMP_FILE),
+        )
+        .unwrap();
+        let info = get_info_from_path(package.path()).unwrap();
+        assert_eq!(info, stamped);
+        assert_eq!(info.version.get_semver().unwrap(), "v1.2.3");
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(get_info_from_path(empty.path()).is_err());
+    }
+
+    #[test]
+    fn malformed_stamp() {
+        let package = tempfile::tempdir().unwrap();
+        std::fs::write(package.path().join(STAMP_FILE), "{").unwrap();
+        assert!(matches!(
+            get_info_from_path(package.path()),
+            Err(GitflowError::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+//! Index of the version tags in a repository
+
+use crate::{branch::parse_train, graph, GitflowConfig, SemverBase, VersionInfo};
+use git2::{ObjectType, Oid, Repository};
+use std::collections::HashMap;
+
+/// A tag whose name parses as a version
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct VersionTag {
+    /// Short name of the tag, e.g. `v1.2.3`
+    pub name: String,
+    pub version: VersionInfo,
+    /// Commit the tag points at, with annotated tags peeled
+    pub commit: Oid,
+}
+
+/// All version tags in a repository, read once so that repeated lookups don't rescan the refs
+#[derive(Debug, Clone, Default)]
+pub struct TagIndex {
+    tags: Vec<VersionTag>,
+    /// Replace refs to follow in [`TagIndex::reachable_from`], if
+    /// [`GitflowConfig::follow_replace_refs`] is set
+    replacements: Option<HashMap<Oid, Oid>>,
+}
+
+impl TagIndex {
+    /// Reads every tag in `repo`, ignoring tags whose names aren't versions, tags rejected by
+    /// [`GitflowConfig::tag_filter`], and if [`GitflowConfig::require_annotated_tags`] is set,
+    /// lightweight tags. Tags that don't point at a commit are ignored too.
+    pub fn new(repo: &Repository, config: &GitflowConfig) -> Result<Self, git2::Error> {
+        Self::with_parser(repo, config, |name| {
+            config
+                .parse_version(name)
+                .or_else(|| parse_train(name, config).map(VersionInfo::Production))
+        })
+    }
+
+    /// Reads the tags of `repo` like [`TagIndex::new`], with `parse` giving the version of each
+    /// tag name, such as one after a package prefix
+    pub(crate) fn with_parser(
+        repo: &Repository,
+        config: &GitflowConfig,
+        mut parse: impl FnMut(&str) -> Option<VersionInfo>,
+    ) -> Result<Self, git2::Error> {
+        let mut tags = Vec::new();
+        for name in repo.tag_names(None)?.iter().flatten() {
+            if let Some(filter) = &config.tag_filter {
+                if !filter.accepts(name) {
+                    continue;
+                }
+            }
+            let version = match parse(name) {
+                Some(version) => version,
+                None => continue,
+            };
+            let reference = repo.find_reference(&format!("refs/tags/{}", name))?;
+            if config.require_annotated_tags {
+                let annotated = reference
+                    .target()
+                    .is_some_and(|id| repo.find_tag(id).is_ok());
+                if !annotated {
+                    continue;
+                }
+            }
+            let target = reference.peel(ObjectType::Any)?;
+            if target.kind() != Some(ObjectType::Commit) {
+                continue;
+            }
+            tags.push(VersionTag {
+                name: name.to_owned(),
+                version,
+                commit: target.id(),
+            });
+        }
+        let replacements = if config.follow_replace_refs {
+            Some(graph::replace_refs(repo)?)
+        } else {
+            None
+        };
+        Ok(Self { tags, replacements })
+    }
+
+    pub fn tags(&self) -> &[VersionTag] {
+        &self.tags
+    }
+
+    /// Returns the version tags pointing at `commit`
+    pub fn at(&self, commit: Oid) -> impl Iterator<Item = &VersionTag> {
+        self.tags.iter().filter(move |tag| tag.commit == commit)
+    }
+
+    /// Returns the version tags on `commit` or any of its ancestors
+    pub fn reachable_from(
+        &self,
+        repo: &Repository,
+        commit: Oid,
+    ) -> Result<Vec<&VersionTag>, git2::Error> {
+        if let Some(replacements) = self.replacements.as_ref().filter(|r| !r.is_empty()) {
+            let history = graph::replaced_history(repo, commit, replacements)?;
+            return Ok(self
+                .tags
+                .iter()
+                .filter(|tag| history.contains(&tag.commit))
+                .collect());
+        }
+        let mut reachable = Vec::new();
+        for tag in &self.tags {
+            if tag.commit == commit || repo.graph_descendant_of(commit, tag.commit)? {
+                reachable.push(tag);
+            }
+        }
+        Ok(reachable)
+    }
+
+    /// Returns the base versions of all production tags
+    pub fn production(&self) -> impl Iterator<Item = SemverBase> + '_ {
+        self.tags.iter().filter_map(|tag| match tag.version {
+            VersionInfo::Production(base) => Some(base),
+            _ => None,
+        })
+    }
+
+    /// Returns the highest production version tagged in the repository
+    pub fn latest_production(&self) -> Option<SemverBase> {
+        self.production().max()
+    }
+
+    /// Returns the highest release candidate number tagged for `base` in the history of `commit`,
+    /// so that older commits on a release branch resolve to older release candidates
+    pub fn latest_rc(
+        &self,
+        repo: &Repository,
+        commit: Oid,
+        base: SemverBase,
+    ) -> Result<Option<u8>, git2::Error> {
+        Ok(self
+            .reachable_from(repo, commit)?
+            .into_iter()
+            .filter_map(|tag| match tag.version {
+                VersionInfo::Alpha(rc) if rc.base == base => Some(rc.rc),
+                _ => None,
+            })
+            .max())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{test_util::TestRepo, TagFilter};
+
+    fn repo() -> TestRepo {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.commit("release");
+        repo.tag_annotated("v1.1.0");
+        repo
+    }
+
+    #[test]
+    fn lightweight_tags_accepted() {
+        let repo = repo();
+        let tags = TagIndex::new(&repo.repo, &GitflowConfig::default()).unwrap();
+        let names: Vec<_> = tags.tags().iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["v1.0.0", "v1.1.0"]);
+    }
+
+    #[test]
+    fn non_commit_tags_ignored() {
+        let repo = repo();
+        let tree = repo.repo.head().unwrap().peel_to_tree().unwrap();
+        repo.repo
+            .tag_lightweight("v2.0.0", tree.as_object(), false)
+            .unwrap();
+        let tags = TagIndex::new(&repo.repo, &GitflowConfig::default()).unwrap();
+        let names: Vec<_> = tags.tags().iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["v1.0.0", "v1.1.0"]);
+    }
+
+    #[test]
+    fn annotated_tags_required() {
+        let repo = repo();
+        let config = GitflowConfig {
+            require_annotated_tags: true,
+            ..Default::default()
+        };
+        let tags = TagIndex::new(&repo.repo, &config).unwrap();
+        let names: Vec<_> = tags.tags().iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, ["v1.1.0"]);
+        // The annotated tag is peeled to the commit it points at
+        assert_eq!(
+            tags.tags()[0].commit,
+            repo.repo.head().unwrap().target().unwrap()
+        );
+    }
+
+    #[test]
+    fn tag_filter() {
+        let repo = repo();
+        repo.tag("v1.1.0-rc.1");
+        repo.commit("mistagged");
+        repo.tag("v1.2.0");
+        let names = |config: &GitflowConfig| -> Vec<String> {
+            let tags = TagIndex::new(&repo.repo, config).unwrap();
+            tags.tags().iter().map(|tag| tag.name.clone()).collect()
+        };
+        assert_eq!(
+            names(&GitflowConfig::default()),
+            ["v1.0.0", "v1.1.0", "v1.1.0-rc.1", "v1.2.0"]
+        );
+
+        // Both rejected tags are versions the index would otherwise include
+        let config = GitflowConfig {
+            tag_filter: Some(TagFilter::new(|name| {
+                name != "v1.2.0" && !name.contains("-rc.")
+            })),
+            ..Default::default()
+        };
+        assert_eq!(names(&config), ["v1.0.0", "v1.1.0"]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn custom_tag_regex() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("rel_1_2_0");
+        repo.tag("v9.9.9");
+        repo.tag("rel_300_0_0");
+        repo.branch("develop");
+        repo.branch("release/rel_1_3_0");
+        repo.commit("fix");
+        repo.tag("rel_1_3_0_rc2");
+        repo.commit("another fix");
+        let config = GitflowConfig {
+            tag_regex: Some(
+                regex::Regex::new(
+                    r"^rel_(?P<major>\d+)_(?P<minor>\d+)_(?P<patch>\d+)(_rc(?P<rc>\d+))?$",
+                )
+                .unwrap(),
+            ),
+            ..Default::default()
+        };
+        let base = |minor| SemverBase {
+            major: 1,
+            minor,
+            patch: 0,
+        };
+
+        // The default grammar no longer applies, and components must fit their fields
+        let tags = TagIndex::new(&repo.repo, &config).unwrap();
+        let versions: Vec<_> = tags.tags().iter().map(|tag| tag.version).collect();
+        assert_eq!(
+            versions,
+            [
+                VersionInfo::Production(base(2)),
+                VersionInfo::Alpha(crate::SemverRC {
+                    base: base(3),
+                    rc: 2
+                }),
+            ]
+        );
+
+        assert_eq!(
+            crate::classify_branch("release/rel_1_3_0", &config),
+            crate::BranchClass::Release(base(3))
+        );
+        let info = crate::get_info_with_config(repo.dir.path(), &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.3.0-rc.3");
+    }
+}
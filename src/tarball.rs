@@ -0,0 +1,95 @@
+//! Versions of source tarballs downloaded from GitHub, which have no git metadata
+
+use crate::{
+    abbreviate_hash, parse_semver, GitflowConfig, GitflowError, GitflowInfo, VersionInfo,
+    VERSION_FILE,
+};
+use std::path::Path;
+
+/// Environment variable giving the version of a build from a source tarball, e.g. `1.2.3` or
+/// `v1.2.3-rc.4`. See [`GitflowConfig::tarball_fallback`](crate::GitflowConfig::tarball_fallback).
+pub const VERSION_ENV_VAR: &str = "GITFLOW_VERSION";
+
+/// Versions a source tarball extracted to `path`, or one of its parents, from the commit embedded
+/// in the directory name and a version from [`VERSION_ENV_VAR`] or the [`VERSION_FILE`] of that
+/// directory. Returns `None` if no directory name embeds a commit or there is no version.
+pub(crate) fn tarball_info(
+    path: &Path,
+    config: &GitflowConfig,
+) -> Result<Option<GitflowInfo>, GitflowError> {
+    let path = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned());
+    let (dir, commit) = match path.ancestors().find_map(|dir| {
+        let commit = embedded_commit(dir.file_name()?.to_str()?)?;
+        Some((dir, commit))
+    }) {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+    let version = match std::env::var(VERSION_ENV_VAR) {
+        Ok(version) => version,
+        Err(_) => match std::fs::read_to_string(dir.join(VERSION_FILE)) {
+            Ok(version) => version,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(GitflowError::Io(err)),
+        },
+    };
+    let version = version.trim();
+    let version = if version.starts_with('v') {
+        parse_semver(version)
+    } else {
+        parse_semver(&format!("v{}", version))
+    };
+    let version = match version {
+        Ok(version @ (VersionInfo::Production(_) | VersionInfo::Alpha(_))) => version,
+        _ => return Ok(None),
+    };
+    let mut info = GitflowInfo::without_repo(version, 0);
+    info.commit_hash = abbreviate_hash(commit.to_owned(), config);
+    Ok(Some(info))
+}
+
+/// Extracts the commit from a directory named like a GitHub tarball, `<owner>-<repo>-<sha>`,
+/// where the part after the last `-` is 7 to 40 lowercase hex digits
+fn embedded_commit(name: &str) -> Option<&str> {
+    let (_, sha) = name.rsplit_once('-')?;
+    let hex = sha
+        .bytes()
+        .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b));
+    Some(sha).filter(|sha| (7..=40).contains(&sha.len()) && hex)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::get_info_with_config;
+
+    #[test]
+    fn commit_in_directory_name() {
+        assert_eq!(
+            embedded_commit("TroyNeubauer-gitflow-rs-0123abc"),
+            Some("0123abc")
+        );
+        assert_eq!(embedded_commit("gitflow-rs-0123abc"), Some("0123abc"));
+        assert_eq!(embedded_commit("gitflow-rs"), None);
+        assert_eq!(embedded_commit("gitflow-rs-cafe"), None);
+        assert_eq!(embedded_commit("gitflow-rs-0123ABC"), None);
+        assert_eq!(embedded_commit("0123abc"), None);
+    }
+
+    #[test]
+    fn tarball_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("TroyNeubauer-gitflow-rs-89abcde");
+        let crate_dir = root.join("crates/core");
+        std::fs::create_dir_all(&crate_dir).unwrap();
+        std::fs::write(root.join(VERSION_FILE), "1.4.0\n").unwrap();
+
+        let mut config = GitflowConfig::default();
+        assert!(get_info_with_config(&crate_dir, &config).is_err());
+
+        config.tarball_fallback = true;
+        let info = get_info_with_config(&crate_dir, &config).unwrap();
+        assert_eq!(info.version.get_semver().unwrap(), "v1.4.0");
+        assert_eq!(info.commit_hash, "89abcde");
+    }
+}
@@ -0,0 +1,185 @@
+//! Helpers for building throwaway repositories in tests
+
+use crate::{GitflowInfo, VersionInfo};
+use git2::{Oid, Repository, Signature, Time};
+use std::cell::Cell;
+use tempfile::TempDir;
+
+pub struct TestRepo {
+    pub dir: TempDir,
+    pub repo: Repository,
+    /// Time of the next commit, which advances with each commit so histories are ordered
+    time: Cell<i64>,
+    /// Name and email of the author and committer of the next commit
+    author: Cell<(&'static str, &'static str)>,
+}
+
+impl TestRepo {
+    /// Creates an empty repository whose HEAD points at an unborn `main`
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.set_head("refs/heads/main").unwrap();
+        Self {
+            dir,
+            repo,
+            time: Cell::new(1_600_000_000),
+            author: Cell::new(("Test", "test@example.com")),
+        }
+    }
+
+    fn signature(&self) -> Signature<'static> {
+        let time = self.time.get();
+        self.time.set(time + 60);
+        let (name, email) = self.author.get();
+        Signature::new(name, email, &Time::new(time, 0)).unwrap()
+    }
+
+    /// Commits onto HEAD like [`TestRepo::commit`] with a commit time of `time`
+    pub fn commit_at(&self, message: &str, time: i64) -> Oid {
+        self.time.set(time);
+        self.commit(message)
+    }
+
+    /// Commits onto HEAD like [`TestRepo::commit`] as the author `name` and `email`
+    pub fn commit_as(&self, message: &str, name: &'static str, email: &'static str) -> Oid {
+        let previous = self.author.replace((name, email));
+        let oid = self.commit(message);
+        self.author.set(previous);
+        oid
+    }
+
+    /// Commits onto HEAD, replacing the contents of `file` with `message`
+    pub fn commit(&self, message: &str) -> Oid {
+        let parents = match self.repo.head() {
+            Ok(head) => vec![head.peel_to_commit().unwrap()],
+            Err(_) => Vec::new(),
+        };
+        let parents: Vec<_> = parents.iter().collect();
+        self.commit_with_parents(message, &parents)
+    }
+
+    /// Commits `contents` to the top level file `path`, keeping the rest of the HEAD tree
+    pub fn commit_file(&self, path: &str, contents: &str, message: &str) -> Oid {
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        let mut tree = self.repo.treebuilder(Some(&head.tree().unwrap())).unwrap();
+        let blob = self.repo.blob(contents.as_bytes()).unwrap();
+        tree.insert(path, blob, 0o100644).unwrap();
+        let tree = self.repo.find_tree(tree.write().unwrap()).unwrap();
+        let sig = self.signature();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &[&head])
+            .unwrap()
+    }
+
+    /// Commits a merge of the commit `other` resolves to into HEAD
+    pub fn merge(&self, other: &str, message: &str) -> Oid {
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        let other = self
+            .repo
+            .revparse_single(other)
+            .unwrap()
+            .peel_to_commit()
+            .unwrap();
+        self.commit_with_parents(message, &[&head, &other])
+    }
+
+    fn commit_with_parents(&self, message: &str, parents: &[&git2::Commit]) -> Oid {
+        let blob = self.repo.blob(message.as_bytes()).unwrap();
+        let base = parents.first().map(|parent| parent.tree().unwrap());
+        let mut tree = self.repo.treebuilder(base.as_ref()).unwrap();
+        tree.insert("file", blob, 0o100644).unwrap();
+        let tree = self.repo.find_tree(tree.write().unwrap()).unwrap();
+        let sig = self.signature();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, parents)
+            .unwrap()
+    }
+
+    /// Commits an uninitialized submodule at `name` pinned to `pin`, writing `.gitmodules` and the
+    /// index the way `git submodule add` would
+    pub fn commit_submodule(&self, name: &str, pin: Oid) -> Oid {
+        let gitmodules = format!("[submodule \"{0}\"]\n\tpath = {0}\n\turl = ../{0}\n", name);
+        std::fs::write(self.dir.path().join(".gitmodules"), &gitmodules).unwrap();
+
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        let mut tree = self.repo.treebuilder(Some(&head.tree().unwrap())).unwrap();
+        let blob = self.repo.blob(gitmodules.as_bytes()).unwrap();
+        tree.insert(".gitmodules", blob, 0o100644).unwrap();
+        tree.insert(name, pin, 0o160000).unwrap();
+        let tree = self.repo.find_tree(tree.write().unwrap()).unwrap();
+
+        let mut index = self.repo.index().unwrap();
+        index.read_tree(&tree).unwrap();
+        index.write().unwrap();
+
+        let sig = self.signature();
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, "add submodule", &tree, &[&head])
+            .unwrap()
+    }
+
+    /// Creates a branch at HEAD and switches to it
+    pub fn branch(&self, name: &str) {
+        let head = self.repo.head().unwrap().peel_to_commit().unwrap();
+        self.repo.branch(name, &head, false).unwrap();
+        self.checkout(name);
+    }
+
+    /// Switches to a new branch with no commits, like `git checkout --orphan`
+    pub fn orphan_branch(&self, name: &str) {
+        self.checkout(name);
+    }
+
+    /// Points HEAD at an existing branch
+    pub fn checkout(&self, name: &str) {
+        self.repo.set_head(&format!("refs/heads/{}", name)).unwrap();
+    }
+
+    /// Creates a lightweight tag at HEAD
+    pub fn tag(&self, name: &str) {
+        let head = self
+            .repo
+            .head()
+            .unwrap()
+            .peel(git2::ObjectType::Commit)
+            .unwrap();
+        self.repo.tag_lightweight(name, &head, false).unwrap();
+    }
+
+    /// Creates an annotated tag at HEAD
+    pub fn tag_annotated(&self, name: &str) {
+        let head = self
+            .repo
+            .head()
+            .unwrap()
+            .peel(git2::ObjectType::Commit)
+            .unwrap();
+        self.repo
+            .tag(name, &head, &self.signature(), name, false)
+            .unwrap();
+    }
+}
+
+/// Builds an info for `version` on `main` without touching a repository
+pub fn info(version: VersionInfo, build_number: u64) -> GitflowInfo {
+    GitflowInfo {
+        branch_name: "main".to_owned(),
+        version,
+        commit_hash: "0123456789abcdef0123456789abcdef01234567".to_owned(),
+        tree_hash: "89abcdef0123456789abcdef0123456789abcdef".to_owned(),
+        commit_time: 1_600_000_000,
+        build_number,
+        dev_version: None,
+        merged_branch: None,
+        merged_pull_request: None,
+        submodule_pins: Vec::new(),
+        base_branch: None,
+        tree_file_count: 0,
+        build_counter: None,
+        author: "Test <test@example.com>".to_owned(),
+        committer: "Test <test@example.com>".to_owned(),
+        history_rewritten: false,
+        codename: None,
+    }
+}
@@ -0,0 +1,512 @@
+//! Linting of repositories against the gitflow model
+
+use crate::{
+    classify_branch, format_hash, local_branches, BranchClass, GitflowConfig, GitflowError,
+    SemverBase, TagIndex, VersionInfo, VersionTag,
+};
+use git2::{Oid, Repository};
+
+/// The kind of rule a [`GitflowViolation`] breaks
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
+pub enum ViolationKind {
+    /// A version tag points at a commit that only exists on develop. Releases should be tagged on
+    /// release, hotfix, or production branches.
+    TagOnDevelop,
+
+    /// A feature branch has a name that looks like a version, which is easily confused with a
+    /// release branch
+    VersionLikeFeatureBranch,
+
+    /// A commit was made directly on a production branch rather than merged into it
+    DirectCommitOnProduction,
+}
+
+/// A state of the repository that doesn't conform to gitflow
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub struct GitflowViolation {
+    pub kind: ViolationKind,
+
+    /// Full name of the offending ref, e.g. `refs/tags/v1.2.3`
+    pub reference: String,
+
+    /// Human readable explanation of the problem
+    pub description: String,
+}
+
+/// Checks `repo` for states that break the gitflow model, for teams that want to enforce it
+/// strictly. An empty result means no violations were found.
+///
+/// The following are reported:
+/// - Version tags on commits that are on develop but not on any release, hotfix, or production
+///   branch
+/// - Feature branches whose name, or a `/` separated component of it, is a version
+/// - Non merge commits on the first parent history of production branches, other than the root
+///   commit
+pub fn validate_gitflow(
+    repo: &Repository,
+    config: &GitflowConfig,
+) -> Result<Vec<GitflowViolation>, GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let branches: Vec<(String, BranchClass, Oid)> = local_branches(repo)?
+        .into_iter()
+        .map(|(name, tip)| {
+            let class = classify_branch(&name, config);
+            (name, class, tip)
+        })
+        .collect();
+    let mut violations = Vec::new();
+
+    let develop: Vec<Oid> = branches
+        .iter()
+        .filter(|(_, class, _)| *class == BranchClass::Develop)
+        .map(|(_, _, tip)| *tip)
+        .collect();
+    let released: Vec<Oid> = branches
+        .iter()
+        .filter(|(_, class, _)| {
+            matches!(
+                class,
+                BranchClass::Production
+                    | BranchClass::Release(_)
+                    | BranchClass::Hotfix(_)
+                    | BranchClass::Train(_)
+            )
+        })
+        .map(|(_, _, tip)| *tip)
+        .collect();
+    for tag in tags.tags() {
+        let on_develop = reachable_from_any(repo, tag.commit, &develop)?;
+        if on_develop && !reachable_from_any(repo, tag.commit, &released)? {
+            violations.push(GitflowViolation {
+                kind: ViolationKind::TagOnDevelop,
+                reference: format!("refs/tags/{}", tag.name),
+                description: format!(
+                    "Tag {} is on develop but not on any release, hotfix, or production branch",
+                    tag.name
+                ),
+            });
+        }
+    }
+
+    for (name, class, tip) in &branches {
+        match class {
+            BranchClass::Feature
+                if name
+                    .split('/')
+                    .any(|part| config.parse_version(part).is_some()) =>
+            {
+                violations.push(GitflowViolation {
+                    kind: ViolationKind::VersionLikeFeatureBranch,
+                    reference: format!("refs/heads/{}", name),
+                    description: format!(
+                        "Feature branch {} is named like a version but is not a release branch",
+                        name
+                    ),
+                });
+            }
+            BranchClass::Production => {
+                let mut commit = repo.find_commit(*tip)?;
+                while commit.parent_count() > 0 {
+                    if commit.parent_count() == 1 {
+                        violations.push(GitflowViolation {
+                            kind: ViolationKind::DirectCommitOnProduction,
+                            reference: format!("refs/heads/{}", name),
+                            description: format!(
+                                "Commit {} was committed directly on {} instead of merged",
+                                format_hash(commit.id(), config),
+                                name
+                            ),
+                        });
+                    }
+                    commit = commit.parent(0)?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(violations)
+}
+
+/// Lists the names that exist as both a tag and a local branch, such as a release branch `v1.2.3`
+/// that was tagged `v1.2.3` without deleting the branch, in sorted order. Git resolves such short
+/// names to the tag, while this crate always takes the branch as the branch and the tag as the
+/// version tag. [`get_info_for_ref`](crate::get_info_for_ref) resolves a colliding short name to
+/// the branch.
+pub fn detect_ref_collisions(repo: &Repository) -> Result<Vec<String>, GitflowError> {
+    let tags = repo.tag_names(None)?;
+    let mut collisions: Vec<String> = local_branches(repo)?
+        .into_iter()
+        .map(|(name, _)| name)
+        .filter(|name| tags.iter().flatten().any(|tag| tag == name))
+        .collect();
+    collisions.sort();
+    Ok(collisions)
+}
+
+/// Checks that `tag` could be created at HEAD, guarding manual tagging. The rules, each failing
+/// with its own error, are:
+/// - [`GitflowError::InvalidTag`]: `tag` is a production or release candidate version, parsed
+///   like existing tags are, and isn't rejected by [`GitflowConfig::tag_filter`]
+/// - [`GitflowError::TagExists`]: `tag` doesn't exist yet
+/// - [`GitflowError::WrongVersionLine`]: `tag` belongs on the current branch. Release and hotfix
+///   branches take release candidates and the release of their own version, train branches the
+///   release of the train, and production branches releases. Develop and feature branches are
+///   never tagged.
+/// - [`GitflowError::TagNotGreater`]: A release is greater than every existing release. A release
+///   candidate is for a version greater than every existing release, and is greater than the
+///   existing release candidates of its version.
+pub fn validate_proposed_tag(
+    repo: &Repository,
+    tag: &str,
+    config: &GitflowConfig,
+) -> Result<(), GitflowError> {
+    let accepted = config
+        .tag_filter
+        .as_ref()
+        .is_none_or(|filter| filter.accepts(tag));
+    let version = match config.parse_version(tag) {
+        Some(version @ (VersionInfo::Production(_) | VersionInfo::Alpha(_))) if accepted => version,
+        _ => {
+            return Err(GitflowError::InvalidTag {
+                tag: tag.to_owned(),
+            })
+        }
+    };
+    if repo.find_reference(&format!("refs/tags/{}", tag)).is_ok() {
+        return Err(GitflowError::TagExists {
+            tag: tag.to_owned(),
+        });
+    }
+
+    let head = repo.head()?;
+    let branch = head.shorthand().unwrap_or_default().to_owned();
+    if !head.is_branch() {
+        return Err(GitflowError::NoBranch {
+            commit: format_hash(head.peel_to_commit()?.id(), config),
+        });
+    }
+    let on_line = match (classify_branch(&branch, config), version) {
+        (BranchClass::Production, VersionInfo::Production(_)) => true,
+        (
+            BranchClass::Release(base) | BranchClass::Hotfix(base),
+            VersionInfo::Production(tagged),
+        ) => tagged == base,
+        (BranchClass::Release(base) | BranchClass::Hotfix(base), VersionInfo::Alpha(rc)) => {
+            rc.base == base
+        }
+        (BranchClass::Train(base), VersionInfo::Production(tagged)) => tagged == base,
+        _ => false,
+    };
+    if !on_line {
+        return Err(GitflowError::WrongVersionLine {
+            tag: tag.to_owned(),
+            branch,
+        });
+    }
+
+    let tags = TagIndex::new(repo, config)?;
+    let base = match version {
+        VersionInfo::Production(base) => base,
+        VersionInfo::Alpha(rc) => rc.base,
+        _ => unreachable!(),
+    };
+    let not_greater = |existing: &VersionTag| GitflowError::TagNotGreater {
+        tag: tag.to_owned(),
+        existing: existing.name.clone(),
+    };
+    for existing in tags.tags() {
+        match existing.version {
+            VersionInfo::Production(released) if released >= base => {
+                return Err(not_greater(existing))
+            }
+            VersionInfo::Alpha(rc)
+                if version < VersionInfo::Alpha(rc) || version == existing.version =>
+            {
+                return Err(not_greater(existing))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the release candidate tags of `base` are numbered contiguously from `rc.1`, as a
+/// skipped number can mean a release candidate was built but its tag was lost. A version with no
+/// release candidates passes. Otherwise fails with [`GitflowError::RcGap`] listing the numbers
+/// missing below the highest release candidate.
+pub fn validate_rc_sequence(
+    repo: &Repository,
+    base: &SemverBase,
+    config: &GitflowConfig,
+) -> Result<(), GitflowError> {
+    let tags = TagIndex::new(repo, config)?;
+    let mut numbers: Vec<u8> = tags
+        .tags()
+        .iter()
+        .filter_map(|tag| match tag.version {
+            VersionInfo::Alpha(rc) if rc.base == *base => Some(rc.rc),
+            _ => None,
+        })
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    let highest = numbers.last().copied().unwrap_or(0);
+    let missing: Vec<u8> = (1..=highest)
+        .filter(|rc| numbers.binary_search(rc).is_err())
+        .collect();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(GitflowError::RcGap {
+            base: *base,
+            missing,
+        })
+    }
+}
+
+/// Returns true if `commit` is any of `tips` or an ancestor of one of them
+fn reachable_from_any(repo: &Repository, commit: Oid, tips: &[Oid]) -> Result<bool, GitflowError> {
+    for tip in tips {
+        if *tip == commit || repo.graph_descendant_of(*tip, commit)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TestRepo;
+
+    fn kinds(repo: &TestRepo) -> Vec<(ViolationKind, String)> {
+        validate_gitflow(&repo.repo, &GitflowConfig::default())
+            .unwrap()
+            .into_iter()
+            .map(|violation| (violation.kind, violation.reference))
+            .collect()
+    }
+
+    #[test]
+    fn conforming_repo() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.0.0");
+        repo.commit("fix");
+        repo.tag("v1.0.0-rc.1");
+        repo.checkout("main");
+        repo.merge("release/v1.0.0", "Merge branch 'release/v1.0.0'");
+        repo.tag("v1.0.0");
+        assert_eq!(kinds(&repo), []);
+    }
+
+    #[test]
+    fn tag_on_develop() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.tag("v1.0.0");
+        assert_eq!(
+            kinds(&repo),
+            [(ViolationKind::TagOnDevelop, "refs/tags/v1.0.0".to_owned())]
+        );
+    }
+
+    #[test]
+    fn version_like_feature_branch() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.branch("feature/v2.0.0");
+        repo.branch("v2.0.0-rc.1");
+        repo.branch("login-page");
+        let mut violations = kinds(&repo);
+        violations.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            violations,
+            [
+                (
+                    ViolationKind::VersionLikeFeatureBranch,
+                    "refs/heads/feature/v2.0.0".to_owned()
+                ),
+                (
+                    ViolationKind::VersionLikeFeatureBranch,
+                    "refs/heads/v2.0.0-rc.1".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn direct_commit_on_production() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.commit("hotfix without a branch");
+        let violations = validate_gitflow(&repo.repo, &GitflowConfig::default()).unwrap();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, ViolationKind::DirectCommitOnProduction);
+        assert_eq!(violations[0].reference, "refs/heads/main");
+        assert!(violations[0].description.contains("directly on main"));
+    }
+
+    #[test]
+    fn valid_proposed_tags() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        assert!(validate_proposed_tag(&repo.repo, "v1.0.1", &GitflowConfig::default()).is_ok());
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("release/v1.1.0");
+        repo.commit("fix");
+        repo.tag("v1.1.0-rc.1");
+        repo.commit("another fix");
+        assert!(
+            validate_proposed_tag(&repo.repo, "v1.1.0-rc.2", &GitflowConfig::default()).is_ok()
+        );
+        assert!(validate_proposed_tag(&repo.repo, "v1.1.0", &GitflowConfig::default()).is_ok());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn proposed_tags_with_tag_regex() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("rel_1_2_0");
+        repo.branch("release/rel_1_3_0");
+        repo.commit("fix");
+        let config = GitflowConfig {
+            tag_regex: Some(
+                regex::Regex::new(
+                    r"^rel_(?P<major>\d+)_(?P<minor>\d+)_(?P<patch>\d+)(_rc(?P<rc>\d+))?$",
+                )
+                .unwrap(),
+            ),
+            ..Default::default()
+        };
+        assert!(validate_proposed_tag(&repo.repo, "rel_1_3_0_rc1", &config).is_ok());
+        assert!(validate_proposed_tag(&repo.repo, "rel_1_3_0", &config).is_ok());
+        assert!(matches!(
+            validate_proposed_tag(&repo.repo, "v1.3.0", &config),
+            Err(GitflowError::InvalidTag { .. })
+        ));
+        assert!(matches!(
+            validate_proposed_tag(&repo.repo, "rel_1_2_0", &config),
+            Err(GitflowError::TagExists { .. })
+        ));
+    }
+
+    #[test]
+    fn proposed_tags_through_tag_filter() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        let config = GitflowConfig {
+            tag_filter: Some(crate::TagFilter::new(|name| !name.contains("-rc."))),
+            ..Default::default()
+        };
+        assert!(validate_proposed_tag(&repo.repo, "v1.0.0", &config).is_ok());
+        assert!(matches!(
+            validate_proposed_tag(&repo.repo, "v1.0.0-rc.1", &config),
+            Err(GitflowError::InvalidTag { .. })
+        ));
+    }
+
+    #[test]
+    fn rejected_proposed_tags() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.2.0");
+        let check =
+            |tag| validate_proposed_tag(&repo.repo, tag, &GitflowConfig::default()).unwrap_err();
+        assert!(matches!(check("nightly"), GitflowError::InvalidTag { .. }));
+        assert!(matches!(check("v1.2.0"), GitflowError::TagExists { .. }));
+        assert!(matches!(
+            check("v1.1.9"),
+            GitflowError::TagNotGreater { existing, .. } if existing == "v1.2.0"
+        ));
+        assert!(matches!(
+            check("v1.3.0-rc.1"),
+            GitflowError::WrongVersionLine { .. }
+        ));
+
+        repo.branch("release/v1.1.0");
+        repo.commit("fix");
+        assert!(matches!(
+            check("v1.1.0-rc.1"),
+            GitflowError::TagNotGreater { existing, .. } if existing == "v1.2.0"
+        ));
+        repo.branch("release/v1.3.0");
+        repo.tag("v1.3.0-rc.2");
+        repo.commit("fix");
+        assert!(matches!(
+            check("v1.3.0-rc.1"),
+            GitflowError::TagNotGreater { existing, .. } if existing == "v1.3.0-rc.2"
+        ));
+        assert!(matches!(
+            check("v1.4.0-rc.1"),
+            GitflowError::WrongVersionLine { .. }
+        ));
+        repo.branch("develop");
+        assert!(matches!(
+            check("v1.3.0-rc.3"),
+            GitflowError::WrongVersionLine { .. }
+        ));
+    }
+
+    #[test]
+    fn rc_sequence_gaps() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("release/v1.1.0");
+        for rc in [1, 3, 6] {
+            repo.commit("fix");
+            repo.tag(&format!("v1.1.0-rc.{}", rc));
+        }
+        let base = SemverBase {
+            major: 1,
+            minor: 1,
+            patch: 0,
+        };
+        match validate_rc_sequence(&repo.repo, &base, &GitflowConfig::default()) {
+            Err(GitflowError::RcGap { missing, .. }) => assert_eq!(missing, vec![2, 4, 5]),
+            other => panic!("expected a gap, got {:?}", other),
+        }
+
+        repo.commit("fix");
+        repo.tag("v1.0.1-rc.1");
+        let hotfix = SemverBase {
+            minor: 0,
+            patch: 1,
+            ..base
+        };
+        validate_rc_sequence(&repo.repo, &hotfix, &GitflowConfig::default()).unwrap();
+        let unreleased = SemverBase { major: 2, ..base };
+        validate_rc_sequence(&repo.repo, &unreleased, &GitflowConfig::default()).unwrap();
+    }
+
+    #[test]
+    fn ref_collisions() {
+        let repo = TestRepo::new();
+        repo.commit("initial");
+        repo.tag("v1.0.0");
+        repo.branch("develop");
+        repo.commit("feature");
+        repo.branch("v1.1.0");
+        repo.commit("fix");
+        assert!(detect_ref_collisions(&repo.repo).unwrap().is_empty());
+
+        repo.tag("v1.1.0");
+        repo.checkout("develop");
+        assert_eq!(detect_ref_collisions(&repo.repo).unwrap(), ["v1.1.0"]);
+
+        // The short name resolves to the release branch rather than the tag
+        let info =
+            crate::get_info_for_ref(&repo.repo, "v1.1.0", &GitflowConfig::default()).unwrap();
+        assert_eq!(info.branch_name, "v1.1.0");
+        assert!(info.version.is_alpha());
+    }
+}